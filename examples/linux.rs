@@ -0,0 +1,33 @@
+//! Talks to a module on a Linux serial port, e.g. a USB adapter or a pty.
+//!
+//!     cargo run --example linux --features serialport -- /dev/ttyUSB0
+use std::{env, thread, time::Duration};
+
+use hc_08::host::{HostSerial, StdDelay};
+use hc_08::parameters::baudrate::BaudRate;
+use hc_08::Hc08;
+
+fn main() {
+    let path = env::args().nth(1).unwrap_or_else(|| "/dev/ttyUSB0".into());
+    let serial = HostSerial::open(&path, BaudRate::Bps9600).unwrap();
+    let mut hc08 = Hc08::try_new(serial, StdDelay).unwrap();
+
+    println!("{:?}", hc08.get_parameters());
+    println!("{:?}", hc08.query_role());
+    println!("{:?}", hc08.query_connectable());
+
+    let mut hc08 = match hc08.into_peripheral_mode() {
+        Ok(hc08) => hc08,
+        Err(_) => panic!("failed to switch to peripheral mode"),
+    };
+    println!("{:?}", hc08.query_role());
+    println!("{:?}", hc08.query_connectable());
+
+    let mut n = 0;
+    loop {
+        n += 1;
+        println!("{}", n);
+        hc08.write_buffer("hc-08".as_bytes()).unwrap();
+        thread::sleep(Duration::new(1, 0));
+    }
+}
@@ -0,0 +1,22 @@
+//! Runs the driver against the in-memory simulator, no hardware needed.
+//!
+//!     cargo run --example sim --features sim
+use hc_08::sim::{Hc08Sim, NoDelay};
+use hc_08::Hc08;
+
+fn main() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    println!("{:?}", hc08.get_parameters());
+    hc08.change_name("hc-08-sim").unwrap();
+
+    let mut hc08 = match hc08.into_peripheral_mode() {
+        Ok(hc08) => hc08,
+        Err(_) => panic!("failed to switch to peripheral mode"),
+    };
+    println!("{:?}", hc08.query_role());
+    println!("{:?}", hc08.get_service_uuid());
+
+    println!("{:#?}", sim.state());
+}
@@ -0,0 +1,344 @@
+//! A handle whose mode is only known at runtime.
+//!
+//! [`AnyHc08`] wraps the four [`Hc08`] typestates so the mode can be picked
+//! from configuration at boot. Mode-specific commands return
+//! [`Error::WrongMode`] when the handle is in another mode.
+
+use crate::command::AtCommand;
+use crate::config::{Applied, Hc08Config};
+use crate::hal::{Delay, Reconfigure, Serial};
+use crate::parameters::addr::Addr;
+use crate::parameters::baudrate::{BaudRate, Parity, SerialConfig};
+use crate::parameters::connectable::IsConnectable;
+use crate::parameters::led_mode::LedMode;
+use crate::parameters::power_mode::PowerMode;
+use crate::parameters::rf_power::RfPower;
+use crate::parameters::role::Role;
+use crate::parameters::uuid::UUID;
+use crate::parameters::Parameters;
+use crate::{Connectable, Error, Hc08, Hc08Error, Master, NonConnectable, Slave};
+
+/// The four combinations of role and connectability.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Mode {
+    /// Master, connectable.
+    Central,
+    /// Slave, connectable.
+    Peripheral,
+    /// Master, non-connectable.
+    Observer,
+    /// Slave, non-connectable.
+    Broadcast,
+}
+
+impl Mode {
+    pub fn new(role: Role, connectable: IsConnectable) -> Self {
+        match (role, connectable) {
+            (Role::Master, IsConnectable(true)) => Mode::Central,
+            (Role::Slave, IsConnectable(true)) => Mode::Peripheral,
+            (Role::Master, IsConnectable(false)) => Mode::Observer,
+            (Role::Slave, IsConnectable(false)) => Mode::Broadcast,
+        }
+    }
+
+    pub fn role(self) -> Role {
+        match self {
+            Mode::Central | Mode::Observer => Role::Master,
+            Mode::Peripheral | Mode::Broadcast => Role::Slave,
+        }
+    }
+
+    pub fn connectable(self) -> IsConnectable {
+        match self {
+            Mode::Central | Mode::Peripheral => IsConnectable(true),
+            Mode::Observer | Mode::Broadcast => IsConnectable(false),
+        }
+    }
+}
+
+pub enum AnyHc08<S, D> {
+    Central(Hc08<S, D, Master, Connectable>),
+    Peripheral(Hc08<S, D, Slave, Connectable>),
+    Observer(Hc08<S, D, Master, NonConnectable>),
+    Broadcast(Hc08<S, D, Slave, NonConnectable>),
+}
+
+/// Evaluates `$body` with `$hc08` bound to whichever typestate is inside.
+macro_rules! with_hc08 {
+    ($any:expr, $hc08:ident => $body:expr) => {
+        match $any {
+            AnyHc08::Central($hc08) => $body,
+            AnyHc08::Peripheral($hc08) => $body,
+            AnyHc08::Observer($hc08) => $body,
+            AnyHc08::Broadcast($hc08) => $body,
+        }
+    };
+}
+
+impl<S, D> AnyHc08<S, D>
+where
+    S: Serial,
+    D: Delay,
+{
+    pub fn mode(&self) -> Mode {
+        match self {
+            AnyHc08::Central(_) => Mode::Central,
+            AnyHc08::Peripheral(_) => Mode::Peripheral,
+            AnyHc08::Observer(_) => Mode::Observer,
+            AnyHc08::Broadcast(_) => Mode::Broadcast,
+        }
+    }
+
+    /// Switches the module to `mode`. On failure the handle is returned
+    /// unchanged.
+    pub fn into_mode(self, mode: Mode) -> Result<Self, Self> {
+        with_hc08!(self, hc08 => match mode {
+            Mode::Central => hc08.into_central_mode().map(Self::from).map_err(Self::from),
+            Mode::Peripheral => hc08.into_peripheral_mode().map(Self::from).map_err(Self::from),
+            Mode::Observer => hc08.into_observer_mode().map(Self::from).map_err(Self::from),
+            Mode::Broadcast => hc08.into_broadcast_mode().map(Self::from).map_err(Self::from),
+        })
+    }
+
+    /// Brings the module in line with `config`. See [`Hc08::apply`].
+    pub fn apply<'c>(self, config: &Hc08Config<'c>) -> Applied<'c, S, D> {
+        with_hc08!(self, hc08 => hc08.apply(config))
+    }
+
+    pub fn query_mode(&mut self) -> Result<Mode, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.query_mode())
+    }
+
+    pub fn set_timeout(&mut self, timeout_ms: u32) {
+        with_hc08!(self, hc08 => hc08.set_timeout(timeout_ms))
+    }
+
+    pub fn timeout(&self) -> u32 {
+        with_hc08!(self, hc08 => hc08.timeout())
+    }
+
+    pub fn set_verify(&mut self, verify: bool) {
+        with_hc08!(self, hc08 => hc08.set_verify(verify))
+    }
+
+    pub fn verify(&self) -> bool {
+        with_hc08!(self, hc08 => hc08.verify())
+    }
+
+    pub fn write_buffer(&mut self, buffer: &[u8]) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.write_buffer(buffer))
+    }
+
+    pub fn read_buffer(&mut self, buffer: &mut [u8]) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.read_buffer(buffer))
+    }
+
+    pub fn execute<A: AtCommand>(&mut self, command: &A) -> Result<A::Response, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.execute(command))
+    }
+
+    pub fn reboot(&mut self) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.reboot())
+    }
+
+    pub fn is_ok(&mut self) -> bool {
+        with_hc08!(self, hc08 => hc08.is_ok())
+    }
+
+    pub fn change_name(&mut self, name: &str) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.change_name(name))
+    }
+
+    pub fn query_connectable(&mut self) -> Result<IsConnectable, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.query_connectable())
+    }
+
+    pub fn query_role(&mut self) -> Result<Role, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.query_role())
+    }
+
+    pub fn get_parameters(&mut self) -> Result<Parameters, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.get_parameters())
+    }
+
+    pub fn get_version<'a>(&mut self, buffer: &'a mut [u8; 21]) -> Result<&'a str, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.get_version(buffer))
+    }
+
+    pub fn get_name<'a>(&mut self, buffer: &'a mut [u8]) -> Result<&'a str, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.get_name(buffer))
+    }
+
+    pub fn query_serial_config(&mut self) -> Result<SerialConfig, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.query_serial_config())
+    }
+
+    pub fn query_addr(&mut self) -> Result<Addr, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.query_addr())
+    }
+
+    pub fn change_addr(&mut self, addr: Addr) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.change_addr(addr))
+    }
+
+    pub fn query_rf_power(&mut self) -> Result<RfPower, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.query_rf_power())
+    }
+
+    pub fn change_rf_power(&mut self, power: RfPower) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.change_rf_power(power))
+    }
+
+    pub fn query_power_mode(&mut self) -> Result<PowerMode, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.query_power_mode())
+    }
+
+    pub fn change_power_mode(&mut self, mode: PowerMode) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.change_power_mode(mode))
+    }
+
+    pub fn change_auto_sleep(&mut self, seconds: u32) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.change_auto_sleep(seconds))
+    }
+
+    pub fn query_led_mode(&mut self) -> Result<LedMode, Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.query_led_mode())
+    }
+
+    pub fn change_led_mode(&mut self, mode: LedMode) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.change_led_mode(mode))
+    }
+
+    pub fn change_broadcast_data(&mut self, data: &[u8]) -> Result<(), Hc08Error<S>> {
+        match self {
+            AnyHc08::Broadcast(hc08) => hc08.change_broadcast_data(data),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn change_advertising_interval(&mut self, ms: u32) -> Result<(), Hc08Error<S>> {
+        match self {
+            AnyHc08::Peripheral(hc08) => hc08.change_advertising_interval(ms),
+            AnyHc08::Broadcast(hc08) => hc08.change_advertising_interval(ms),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn query_advertising_interval(&mut self) -> Result<u32, Hc08Error<S>> {
+        match self {
+            AnyHc08::Peripheral(hc08) => hc08.query_advertising_interval(),
+            AnyHc08::Broadcast(hc08) => hc08.query_advertising_interval(),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn change_connect_internal(&mut self, min: u32, max: u32) -> Result<(), Hc08Error<S>> {
+        match self {
+            AnyHc08::Central(hc08) => hc08.change_connect_internal(min, max),
+            AnyHc08::Peripheral(hc08) => hc08.change_connect_internal(min, max),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn change_connect_timeout(&mut self, time: u32) -> Result<(), Hc08Error<S>> {
+        match self {
+            AnyHc08::Central(hc08) => hc08.change_connect_timeout(time),
+            AnyHc08::Peripheral(hc08) => hc08.change_connect_timeout(time),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn clear_slave_addr(&mut self) -> Result<(), Hc08Error<S>> {
+        match self {
+            AnyHc08::Central(hc08) => hc08.clear_slave_addr(),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn query_connect_uuid(&mut self) -> Result<UUID, Hc08Error<S>> {
+        match self {
+            AnyHc08::Central(hc08) => hc08.query_connect_uuid(),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn set_connect_uuid(&mut self, uuid: UUID) -> Result<(), Hc08Error<S>> {
+        match self {
+            AnyHc08::Central(hc08) => hc08.set_connect_uuid(uuid),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn get_service_uuid(&mut self) -> Result<UUID, Hc08Error<S>> {
+        match self {
+            AnyHc08::Peripheral(hc08) => hc08.get_service_uuid(),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn set_service_uuid(&mut self, uuid: UUID) -> Result<(), Hc08Error<S>> {
+        match self {
+            AnyHc08::Peripheral(hc08) => hc08.set_service_uuid(uuid),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn get_characteristic_uuid(&mut self) -> Result<UUID, Hc08Error<S>> {
+        match self {
+            AnyHc08::Peripheral(hc08) => hc08.get_characteristic_uuid(),
+            _ => Err(Error::WrongMode),
+        }
+    }
+
+    pub fn set_characteristic_uuid(&mut self, uuid: UUID) -> Result<(), Hc08Error<S>> {
+        match self {
+            AnyHc08::Peripheral(hc08) => hc08.set_characteristic_uuid(uuid),
+            _ => Err(Error::WrongMode),
+        }
+    }
+}
+
+impl<S, D> AnyHc08<S, D>
+where
+    S: Reconfigure,
+    D: Delay,
+{
+    pub fn change_baud_rate(
+        &mut self,
+        baud_rate: BaudRate,
+        parity: Parity,
+    ) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.change_baud_rate(baud_rate, parity))
+    }
+
+    pub fn change_serial_config(&mut self, config: SerialConfig) -> Result<(), Hc08Error<S>> {
+        with_hc08!(self, hc08 => hc08.change_serial_config(config))
+    }
+}
+
+macro_rules! impl_conversions {
+    ($variant:ident, $role:ty, $connectable:ty) => {
+        impl<S, D> From<Hc08<S, D, $role, $connectable>> for AnyHc08<S, D> {
+            fn from(hc08: Hc08<S, D, $role, $connectable>) -> Self {
+                AnyHc08::$variant(hc08)
+            }
+        }
+
+        /// Fails with the handle unchanged if it is in another mode.
+        impl<S, D> TryFrom<AnyHc08<S, D>> for Hc08<S, D, $role, $connectable> {
+            type Error = AnyHc08<S, D>;
+
+            fn try_from(any: AnyHc08<S, D>) -> Result<Self, Self::Error> {
+                match any {
+                    AnyHc08::$variant(hc08) => Ok(hc08),
+                    other => Err(other),
+                }
+            }
+        }
+    };
+}
+
+impl_conversions!(Central, Master, Connectable);
+impl_conversions!(Peripheral, Slave, Connectable);
+impl_conversions!(Observer, Master, NonConnectable);
+impl_conversions!(Broadcast, Slave, NonConnectable);
@@ -0,0 +1,358 @@
+//! Async driver built on embedded-io-async and embedded-hal-async.
+//!
+//! [`AsyncHc08`] mirrors [`Hc08`](crate::Hc08) method for method, but every
+//! command yields while the module is answering instead of busy-waiting.
+
+use core::future::{poll_fn, Future};
+use core::marker::PhantomData;
+use core::pin::pin;
+use core::task::Poll;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_io_async::{Read, Write};
+
+use crate::any::Mode;
+use crate::command::{
+    AtCommand, ChangeBroadcastData, ChangeConnectInternal, ChangeConnectTimeout, ChangeConnectable,
+    ChangeName, ChangeRole, ChangeUuid, ClearAddr, QueryConnectable, QueryName, QueryParameters,
+    QueryRole, QueryUuid, QueryVersion, ResetSettings, Test, UuidSlot, Verify,
+};
+use crate::parameters::connectable::IsConnectable;
+use crate::parameters::uuid::UUID;
+use crate::parameters::{role::Role, Parameters};
+use crate::response::ResponseReader;
+use crate::{
+    copy_str, Connectable, Error, Master, NonConnectable, Slave, COMMAND_LEN, DEFAULT_TIMEOUT_MS,
+    LINE_GAP_MS,
+};
+
+pub struct AsyncHc08<S, D, R, C> {
+    serial: S,
+    delay: D,
+    timeout_ms: u32,
+    verify: bool,
+    role: PhantomData<R>,
+    connectable: PhantomData<C>,
+}
+
+impl<S, D> AsyncHc08<S, D, Master, Connectable>
+where
+    S: Read + Write,
+    D: DelayNs,
+{
+    /// Restores the module's factory settings, which leave it as a
+    /// connectable master. On failure the serial port and delay are handed
+    /// back.
+    pub async fn try_new(serial: S, delay: D) -> Result<Self, (Error<S::Error>, S, D)> {
+        let mut result = Self {
+            serial,
+            delay,
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+            verify: false,
+            role: PhantomData::<Master>,
+            connectable: PhantomData::<Connectable>,
+        };
+
+        match result.reset_setting().await {
+            Ok(()) => Ok(result),
+            Err(e) => Err((e, result.serial, result.delay)),
+        }
+    }
+}
+
+type ToCentral<S, D, R, C> = Result<AsyncHc08<S, D, Master, Connectable>, AsyncHc08<S, D, R, C>>;
+type ToPeripheral<S, D, R, C> = Result<AsyncHc08<S, D, Slave, Connectable>, AsyncHc08<S, D, R, C>>;
+type ToObserver<S, D, R, C> =
+    Result<AsyncHc08<S, D, Master, NonConnectable>, AsyncHc08<S, D, R, C>>;
+type ToBroadcast<S, D, R, C> =
+    Result<AsyncHc08<S, D, Slave, NonConnectable>, AsyncHc08<S, D, R, C>>;
+
+/// Resolves to `None` if `timeout` completes before `fut`.
+async fn with_timeout<F, T>(fut: F, timeout: T) -> Option<F::Output>
+where
+    F: Future,
+    T: Future<Output = ()>,
+{
+    let mut fut = pin!(fut);
+    let mut timeout = pin!(timeout);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = fut.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        if timeout.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+impl<S, D, R, C> AsyncHc08<S, D, R, C>
+where
+    S: Read + Write,
+    D: DelayNs,
+{
+    /// Sets how long a read waits for each byte of a response before
+    /// giving up with [`Error::Timeout`].
+    pub fn set_timeout(&mut self, timeout_ms: u32) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    pub fn timeout(&self) -> u32 {
+        self.timeout_ms
+    }
+
+    /// Turns verify mode on or off. See
+    /// [`Hc08::set_verify`](crate::Hc08::set_verify).
+    pub fn set_verify(&mut self, verify: bool) {
+        self.verify = verify;
+    }
+
+    pub fn verify(&self) -> bool {
+        self.verify
+    }
+
+    pub async fn write_buffer(&mut self, buffer: &[u8]) -> Result<(), Error<S::Error>> {
+        self.serial.write_all(buffer).await.map_err(Error::Write)
+    }
+
+    async fn read_byte(&mut self) -> Result<u8, Error<S::Error>> {
+        self.read_byte_within(self.timeout_ms).await
+    }
+
+    async fn read_byte_within(&mut self, timeout_ms: u32) -> Result<u8, Error<S::Error>> {
+        let mut byte = [0u8; 1];
+        let read = self.serial.read(&mut byte);
+        let timeout = self.delay.delay_ms(timeout_ms);
+        match with_timeout(read, timeout).await {
+            Some(Ok(0)) => Err(Error::WrongResponse),
+            Some(Ok(_)) => Ok(byte[0]),
+            Some(Err(e)) => Err(Error::Read(e)),
+            None => Err(Error::Timeout),
+        }
+    }
+
+    pub async fn read_buffer(&mut self, buffer: &mut [u8]) -> Result<(), Error<S::Error>> {
+        for v in buffer.iter_mut() {
+            *v = self.read_byte().await?;
+        }
+        Ok(())
+    }
+
+    /// Sends `command` and decodes the module's answer as it arrives,
+    /// taking a pause of [`LINE_GAP_MS`] as its end. See
+    /// [`Hc08::execute`](crate::Hc08::execute).
+    pub async fn execute<A: AtCommand>(
+        &mut self,
+        command: &A,
+    ) -> Result<A::Response, Error<S::Error>> {
+        let mut buffer = [0u8; COMMAND_LEN];
+        self.write_buffer(command.encode(&mut buffer)?).await?;
+
+        let mut reader = ResponseReader::new(command);
+        let mut timeout_ms = self.timeout_ms;
+        loop {
+            match self.read_byte_within(timeout_ms).await {
+                Ok(byte) => reader.push(byte)?,
+                Err(Error::Timeout) if reader.received() => return reader.finish(),
+                Err(e) => return Err(e),
+            }
+            timeout_ms = LINE_GAP_MS;
+        }
+    }
+
+    /// Runs a setter, then reads the value back if in verify mode.
+    /// The expected value is worked out before the command is sent, so a
+    /// value that could never verify is refused without changing the module.
+    async fn change<A: Verify>(&mut self, command: &A) -> Result<(), Error<S::Error>> {
+        if !self.verify {
+            return self.execute(command).await;
+        }
+
+        let expected = command.expected()?;
+        self.execute(command).await?;
+        let actual = command.actual(self.execute(&command.query()).await?);
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(Error::VerifyMismatch { expected, actual })
+        }
+    }
+
+    pub async fn change_name(&mut self, name: &str) -> Result<(), Error<S::Error>> {
+        self.change(&ChangeName(name)).await
+    }
+
+    pub async fn query_connectable(&mut self) -> Result<IsConnectable, Error<S::Error>> {
+        self.execute(&QueryConnectable).await
+    }
+
+    pub async fn query_role(&mut self) -> Result<Role, Error<S::Error>> {
+        self.execute(&QueryRole).await
+    }
+
+    pub async fn reset_setting(&mut self) -> Result<(), Error<S::Error>> {
+        self.execute(&ResetSettings).await
+    }
+
+    pub async fn is_ok(&mut self) -> bool {
+        self.execute(&Test).await.is_ok()
+    }
+
+    pub async fn get_parameters(&mut self) -> Result<Parameters, Error<S::Error>> {
+        self.execute(&QueryParameters).await
+    }
+
+    /// The firmware version, copied into `buffer`.
+    pub async fn get_version<'a>(
+        &mut self,
+        buffer: &'a mut [u8; 21],
+    ) -> Result<&'a str, Error<S::Error>> {
+        let version = self.execute(&QueryVersion).await?;
+        copy_str(version.as_str(), buffer)
+    }
+
+    /// The device name, copied into `buffer`.
+    pub async fn get_name<'a>(&mut self, buffer: &'a mut [u8]) -> Result<&'a str, Error<S::Error>> {
+        let name = self.execute(&QueryName).await?;
+        copy_str(name.as_str(), buffer)
+    }
+
+    pub async fn change_role(&mut self, role: Role) -> Result<(), Error<S::Error>> {
+        self.change(&ChangeRole(role)).await
+    }
+
+    pub async fn change_connectable(&mut self, c: IsConnectable) -> Result<(), Error<S::Error>> {
+        self.change(&ChangeConnectable(c)).await
+    }
+
+    fn into_state<R2, C2>(self) -> AsyncHc08<S, D, R2, C2> {
+        AsyncHc08 {
+            serial: self.serial,
+            delay: self.delay,
+            timeout_ms: self.timeout_ms,
+            verify: self.verify,
+            role: PhantomData,
+            connectable: PhantomData,
+        }
+    }
+
+    pub async fn query_mode(&mut self) -> Result<Mode, Error<S::Error>> {
+        let role = self.query_role().await?;
+        let connectable = self.query_connectable().await?;
+        Ok(Mode::new(role, connectable))
+    }
+
+    /// Changes role and connectability, then reads both back to make sure
+    /// the module really is in `mode`.
+    async fn switch_mode(&mut self, mode: Mode) -> Result<(), Error<S::Error>> {
+        self.change_role(mode.role()).await?;
+        self.delay.delay_ms(200).await;
+
+        self.change_connectable(mode.connectable()).await?;
+        self.delay.delay_ms(200).await;
+
+        if self.query_mode().await? == mode {
+            Ok(())
+        } else {
+            Err(Error::WrongResponse)
+        }
+    }
+
+    pub async fn into_central_mode(mut self) -> ToCentral<S, D, R, C> {
+        match self.switch_mode(Mode::Central).await {
+            Ok(()) => Ok(self.into_state()),
+            Err(_) => Err(self),
+        }
+    }
+
+    pub async fn into_peripheral_mode(mut self) -> ToPeripheral<S, D, R, C> {
+        match self.switch_mode(Mode::Peripheral).await {
+            Ok(()) => Ok(self.into_state()),
+            Err(_) => Err(self),
+        }
+    }
+
+    pub async fn into_observer_mode(mut self) -> ToObserver<S, D, R, C> {
+        match self.switch_mode(Mode::Observer).await {
+            Ok(()) => Ok(self.into_state()),
+            Err(_) => Err(self),
+        }
+    }
+
+    pub async fn into_broadcast_mode(mut self) -> ToBroadcast<S, D, R, C> {
+        match self.switch_mode(Mode::Broadcast).await {
+            Ok(()) => Ok(self.into_state()),
+            Err(_) => Err(self),
+        }
+    }
+}
+
+impl<S, D> AsyncHc08<S, D, Slave, NonConnectable>
+where
+    S: Read + Write,
+    D: DelayNs,
+{
+    pub async fn change_broadcast_data(&mut self, data: &[u8]) -> Result<(), Error<S::Error>> {
+        self.change(&ChangeBroadcastData(data)).await
+    }
+}
+
+impl<S, D, R> AsyncHc08<S, D, R, Connectable>
+where
+    S: Read + Write,
+    D: DelayNs,
+{
+    pub async fn change_connect_internal(
+        &mut self,
+        min: u32,
+        max: u32,
+    ) -> Result<(), Error<S::Error>> {
+        self.execute(&ChangeConnectInternal { min, max }).await
+    }
+
+    pub async fn change_connect_timeout(&mut self, time: u32) -> Result<(), Error<S::Error>> {
+        self.execute(&ChangeConnectTimeout(time)).await
+    }
+}
+
+impl<S, D> AsyncHc08<S, D, Master, Connectable>
+where
+    S: Read + Write,
+    D: DelayNs,
+{
+    pub async fn clear_slave_addr(&mut self) -> Result<(), Error<S::Error>> {
+        self.execute(&ClearAddr).await
+    }
+
+    pub async fn query_connect_uuid(&mut self) -> Result<UUID, Error<S::Error>> {
+        self.execute(&QueryUuid(UuidSlot::Connect)).await
+    }
+
+    pub async fn set_connect_uuid(&mut self, uuid: UUID) -> Result<(), Error<S::Error>> {
+        self.change(&ChangeUuid(UuidSlot::Connect, uuid)).await
+    }
+}
+
+impl<S, D> AsyncHc08<S, D, Slave, Connectable>
+where
+    S: Read + Write,
+    D: DelayNs,
+{
+    pub async fn get_service_uuid(&mut self) -> Result<UUID, Error<S::Error>> {
+        self.execute(&QueryUuid(UuidSlot::Service)).await
+    }
+
+    pub async fn set_service_uuid(&mut self, uuid: UUID) -> Result<(), Error<S::Error>> {
+        self.change(&ChangeUuid(UuidSlot::Service, uuid)).await
+    }
+
+    pub async fn get_characteristic_uuid(&mut self) -> Result<UUID, Error<S::Error>> {
+        self.execute(&QueryUuid(UuidSlot::Characteristic)).await
+    }
+
+    pub async fn set_characteristic_uuid(&mut self, uuid: UUID) -> Result<(), Error<S::Error>> {
+        self.change(&ChangeUuid(UuidSlot::Characteristic, uuid))
+            .await
+    }
+}
@@ -1,39 +1,117 @@
-use crate::parameters::{connectable::IsConnectable, role::Role, uuid::UUID};
+//! The AT commands the module understands.
+//!
+//! Each command is a small value implementing [`AtCommand`], which knows how
+//! to write itself out and how to read the module's answer from the
+//! [`Line`]s it arrives as. [`Hc08::execute`](crate::Hc08::execute) runs any
+//! of them.
+
+use crate::parameters::baudrate::SerialConfig;
+use crate::parameters::broadcast_data::BroadcastData;
+use crate::parameters::{
+    addr::Addr, connectable::IsConnectable, led_mode::LedMode, name::Name, power_mode::PowerMode,
+    rf_power::RfPower, role::Role, uuid::UUID, version::Version, Parameters, ParametersParser,
+    ParseError,
+};
+use crate::response::{Line, Token};
+use core::ops::RangeInclusive;
+
+/// A command, and how to read what the module answers to it.
+///
+/// The answer is handed to [`decode`](Self::decode) one [`Line`] at a time,
+/// then [`finish`](Self::finish) is called once the module has gone quiet.
+/// Unless the command answers with a [bare value](Self::BARE_VALUE), an
+/// `ERROR` line never reaches `decode`; the command fails with
+/// [`Error::Rejected`](crate::Error::Rejected) instead.
+pub trait AtCommand {
+    type Response;
+
+    /// Whether the answer is a bare value, such as a name, taken as it
+    /// arrives. `ERROR` is then only taken as a refusal when it is the whole
+    /// line, since the value may well start that way.
+    const BARE_VALUE: bool = false;
+
+    /// What `decode` builds up across the lines of one answer. Most
+    /// answers are a single line, kept in an `Option<Self::Response>`.
+    type Partial: Default;
+
+    /// Writes the command into `buffer` and returns the bytes to send. Fails
+    /// with [`ParseError::TooLong`] if `buffer` is too small.
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError>;
+
+    /// Takes one line of the answer.
+    fn decode(&self, partial: &mut Self::Partial, line: Line<'_>) -> Result<(), ParseError>;
+
+    /// Turns the lines taken so far into the response.
+    fn finish(&self, partial: Self::Partial) -> Result<Self::Response, ParseError>;
+}
+
+/// A value one of the setters changes, as
+/// [`Error::VerifyMismatch`](crate::Error::VerifyMismatch) reports it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Setting {
+    Name(Name),
+    Role(Role),
+    Connectable(IsConnectable),
+    BroadcastData(BroadcastData),
+    RfPower(RfPower),
+    PowerMode(PowerMode),
+    LedMode(LedMode),
+    Addr(Addr),
+    SerialConfig(SerialConfig),
+    /// In milliseconds, rounded down to the module's 0.625 ms steps.
+    AdvertisingInterval(u32),
+    Uuid(UuidSlot, UUID),
+}
+
+/// A setter with a query that reads its value back, so that verify mode
+/// (see [`Hc08::set_verify`](crate::Hc08::set_verify)) can check the module
+/// really took it.
+pub trait Verify: AtCommand<Response = ()> {
+    type Query: AtCommand;
+
+    /// The query reporting what this command sets.
+    fn query(&self) -> Self::Query;
+
+    /// What the query should report once this command has been taken.
+    fn expected(&self) -> Result<Setting, ParseError>;
+
+    /// What the query did report.
+    fn actual(&self, response: <Self::Query as AtCommand>::Response) -> Setting;
+}
 
 pub const OK_QUERY: [u8; 2] = *b"AT";
-pub const OK_RESPONSE: [u8; 2] = *b"OK";
 
 pub const QUERY_PARAMS_COMMAND: [u8; 5] = *b"AT+RX";
 
 pub const RESET_SETTINGS_COMMAND: [u8; 10] = *b"AT+DEFAULT";
+pub const REBOOT_COMMAND: [u8; 8] = *b"AT+RESET";
 pub const QUERY_VERSION: [u8; 10] = *b"AT+VERSION";
 
 pub const ROLE_BASE: [u8; 8] = *b"AT+ROLE=";
 pub const QUERY_ROLE: [u8; 9] = *b"AT+ROLE=?";
 
-pub fn build_change_role_command(role: Role) -> [u8; 9] {
-    let mut result = [0; 9];
-    result[..8].clone_from_slice(&ROLE_BASE);
-    result[8] = match role {
-        Role::Master => 'M',
-        Role::Slave => 'S',
-    } as u8;
+pub const RF_POWER_BASE: [u8; 8] = *b"AT+RFPM=";
+pub const QUERY_RF_POWER: [u8; 9] = *b"AT+RFPM=?";
 
-    result
-}
+pub const POWER_MODE_BASE: [u8; 8] = *b"AT+MODE=";
+pub const QUERY_POWER_MODE: [u8; 9] = *b"AT+MODE=?";
+
+pub const AUTO_SLEEP_BASE: [u8; 8] = *b"AT+AUST=";
+/// Idle time before the module sleeps in [`PowerMode::LowPower2`], in
+/// seconds.
+pub const AUTO_SLEEP_RANGE_S: RangeInclusive<u32> = 1..=300;
+
+pub const LED_MODE_BASE: [u8; 7] = *b"AT+LED=";
+pub const QUERY_LED_MODE: [u8; 8] = *b"AT+LED=?";
 
 pub const CONNECTABLE_BASE: [u8; 8] = *b"AT+CONT=";
 pub const QUERY_CONNECTABLE: [u8; 9] = *b"AT+CONT=?";
 
-pub fn build_change_connectable_command(c: IsConnectable) -> [u8; 9] {
-    let mut result = [0; 9];
-    result[..8].clone_from_slice(&CONNECTABLE_BASE);
-    result[8] = if c.0 { b'0' } else { b'1' };
-
-    result
-}
+pub const CHANGE_BAUD_RATE_BASE: [u8; 8] = *b"AT+BAUD=";
+pub const QUERY_BAUD_RATE: [u8; 9] = *b"AT+BAUD=?";
 
 pub const CHANGE_BROADCAST_BASE: [u8; 8] = *b"AT+AVDA=";
+pub const QUERY_BROADCAST: [u8; 9] = *b"AT+AVDA=?";
 
 pub const CHANGE_NAME_BASE: [u8; 8] = *b"AT+NAME=";
 pub const QUERY_NAME: [u8; 9] = *b"AT+NAME=?";
@@ -41,136 +119,1022 @@ pub const QUERY_NAME: [u8; 9] = *b"AT+NAME=?";
 pub const CLEAR_ADDR: [u8; 8] = *b"AT+CLEAR";
 
 pub const CHANGE_CONNECT_INTERNAL_BASE: [u8; 8] = *b"AT+CINT=";
-pub const CHANGE_CONNECT_INTERNAL_RESPONSE: [u8; 8] = *b"OK+CINT=";
-pub fn build_change_connect_internal_command<'a>(
-    min: u32,
-    max: u32,
-    buffer: &'a mut [u8],
-) -> &'a [u8] {
-    buffer.clone_from_slice(&CHANGE_CONNECT_INTERNAL_BASE);
-    let mut n = CHANGE_BROADCAST_BASE.len();
-    let (min, max) = (min / 4 * 5, max / 4 * 5);
-    if min == max {
-        n += num2hex(min, &mut buffer[n..]);
+/// Legal connection interval, in milliseconds (6 to 3200 units of 1.25 ms).
+pub const CONNECT_INTERNAL_RANGE_MS: RangeInclusive<u32> = 8..=4000;
+
+/// Converts milliseconds to the module's 1.25 ms connection interval units.
+/// Fails for bounds outside [`CONNECT_INTERNAL_RANGE_MS`], which keeps the
+/// arithmetic from overflowing.
+fn connect_internal_units(ms: u32) -> Result<u32, ParseError> {
+    checked(ms, &CONNECT_INTERNAL_RANGE_MS)?;
+    Ok(ms * 4 / 5)
+}
+
+pub const CHANGE_CONNECT_TIMEOUT_BASE: [u8; 9] = *b"AT+CTOUT=";
+/// Legal connection supervision timeout, in milliseconds (10 to 3200 units
+/// of 10 ms).
+pub const CONNECT_TIMEOUT_RANGE_MS: RangeInclusive<u32> = 100..=32000;
+
+/// Passes `value` through if it lies in `range`, else fails with
+/// [`ParseError::OutOfRange`].
+fn checked(value: u32, range: &RangeInclusive<u32>) -> Result<u32, ParseError> {
+    if range.contains(&value) {
+        Ok(value)
     } else {
-        n += num2hex(min, &mut buffer[n..]);
-        buffer[n] = b',';
-        n += 1;
-        n += num2hex(max, &mut buffer[n..]);
+        Err(ParseError::OutOfRange)
     }
+}
 
-    &buffer[..n]
+pub const ADVERTISING_INTERVAL_BASE: [u8; 8] = *b"AT+AINT=";
+pub const QUERY_ADVERTISING_INTERVAL: [u8; 9] = *b"AT+AINT=?";
+/// Legal advertising interval, in milliseconds (32 to 16384 units of
+/// 0.625 ms).
+pub const ADVERTISING_INTERVAL_RANGE_MS: RangeInclusive<u32> = 20..=10240;
+
+/// Converts milliseconds to the module's 0.625 ms advertising interval units.
+/// Fails for intervals outside [`ADVERTISING_INTERVAL_RANGE_MS`], which
+/// keeps the arithmetic from overflowing.
+fn advertising_interval_units(ms: u32) -> Result<u32, ParseError> {
+    checked(ms, &ADVERTISING_INTERVAL_RANGE_MS)?;
+    Ok(ms * 8 / 5)
+}
+
+/// Converts the module's 0.625 ms advertising interval units to
+/// milliseconds, rounding down. Takes whatever the module reports, so it
+/// works in `u64`; the result always fits back.
+pub fn advertising_interval_ms(units: u32) -> u32 {
+    (u64::from(units) * 5 / 8) as u32
 }
 
-pub fn build_change_connect_internal_response<'a>(
-    min: u32,
-    max: u32,
+pub const CHANGE_ADDR_BASE: [u8; 8] = *b"AT+ADDR=";
+pub const QUERY_ADDR: [u8; 9] = *b"AT+ADDR=?";
+
+/// Builds a command or an expected answer piece by piece, remembering if it
+/// ran out of room.
+struct Encoder<'a> {
     buffer: &'a mut [u8],
-) -> &'a [u8] {
-    buffer.clone_from_slice(&CHANGE_CONNECT_INTERNAL_BASE);
-    let mut n = CHANGE_BROADCAST_BASE.len();
-    let (min, max) = (min / 4 * 5, max / 4 * 5);
+    len: usize,
+    overflow: bool,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            len: 0,
+            overflow: false,
+        }
+    }
+
+    fn bytes(mut self, bytes: &[u8]) -> Self {
+        match self.buffer.get_mut(self.len..self.len + bytes.len()) {
+            Some(dst) => dst.copy_from_slice(bytes),
+            None => self.overflow = true,
+        }
+        self.len += bytes.len();
+        self
+    }
+
+    fn byte(self, byte: u8) -> Self {
+        self.bytes(&[byte])
+    }
+
+    fn num(self, num: u32) -> Self {
+        let mut digits = [0; 10];
+        let n = num2dec(num, &mut digits);
+        self.bytes(&digits[..n])
+    }
 
-    n += num2hex(min, &mut buffer[n..]);
-    buffer[n] = b',';
-    n += 1;
-    n += num2hex(max, &mut buffer[n..]);
+    fn finish(self) -> Result<&'a [u8], ParseError> {
+        if self.overflow {
+            return Err(ParseError::TooLong);
+        }
 
-    &buffer[..n]
+        let buffer: &'a [u8] = self.buffer;
+        Ok(&buffer[..self.len])
+    }
 }
 
-pub const CHANGE_CONNECT_TIMEOUT_BASE: [u8; 9] = *b"AT+CTOUT=";
-pub const CHANGE_CONNECT_TIMEOUT_RESPONSE: [u8; 9] = *b"OK+CTOUT=";
-pub fn build_change_connect_timeout_command<'a>(time: u32, buffer: &'a mut [u8]) -> &'a [u8] {
-    buffer.clone_from_slice(&CHANGE_CONNECT_TIMEOUT_BASE);
-    let mut n = CHANGE_CONNECT_TIMEOUT_BASE.len();
-    let time = time / 10;
+/// Checks for the plain `OK` most setters answer with.
+fn ok(line: Line<'_>) -> Result<(), ParseError> {
+    match line.token()? {
+        Token::Ok => Ok(()),
+        _ => Err(ParseError::WrongValue),
+    }
+}
+
+/// The value of an `OK+KEY=value` echo.
+fn reply<'l>(line: Line<'l>, key: &str) -> Result<&'l str, ParseError> {
+    match line.token()? {
+        Token::Reply { key: k, value } if k == key => Ok(value),
+        Token::Reply { .. } => Err(ParseError::PrefixError),
+        _ => Err(ParseError::WrongValue),
+    }
+}
+
+/// Checks that an `OK+KEY=value` echo repeats `expected`.
+fn echo(line: Line<'_>, key: &str, expected: &[u8]) -> Result<(), ParseError> {
+    if reply(line, key)?.as_bytes() == expected {
+        Ok(())
+    } else {
+        Err(ParseError::WrongValue)
+    }
+}
+
+/// Implements the decoding half of [`AtCommand`] for an answer that is a
+/// single line, turned into the response by `$body`.
+macro_rules! single_line {
+    ($response:ty, |$self:ident, $line:ident| $body:expr) => {
+        type Response = $response;
+        type Partial = Option<$response>;
+
+        fn decode(
+            &$self,
+            partial: &mut Option<$response>,
+            $line: Line<'_>,
+        ) -> Result<(), ParseError> {
+            if partial.is_some() {
+                return Err(ParseError::WrongValue);
+            }
+
+            *partial = Some($body?);
+            Ok(())
+        }
+
+        fn finish(&self, partial: Option<$response>) -> Result<$response, ParseError> {
+            partial.ok_or(ParseError::MissingValue)
+        }
+    };
+}
+
+/// Implements [`AtCommand`] for a command that is a fixed string answered
+/// with `OK`.
+macro_rules! plain_command {
+    ($command:ident, $bytes:expr) => {
+        impl AtCommand for $command {
+            single_line!((), |self, line| ok(line));
+
+            fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+                Encoder::new(buffer).bytes(&$bytes).finish()
+            }
+        }
+    };
+}
+
+/// Implements [`AtCommand`] for a fixed query whose answer is the bare
+/// value, parsed with `TryFrom<&str>`.
+macro_rules! query_command {
+    ($command:ident, $bytes:expr, $response:ty) => {
+        impl AtCommand for $command {
+            const BARE_VALUE: bool = true;
+
+            single_line!($response, |self, line| <$response>::try_from(
+                line.as_str()?
+            ));
+
+            fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+                Encoder::new(buffer).bytes(&$bytes).finish()
+            }
+        }
+    };
+}
 
-    n += num2hex(time, &mut buffer[n..]);
+/// Implements [`AtCommand`] for a setter taking a single code byte after
+/// `$base`, answered with `OK`.
+macro_rules! code_command {
+    ($command:ident, $base:expr, |$value:ident| $code:expr) => {
+        impl AtCommand for $command {
+            single_line!((), |self, line| ok(line));
 
-    &buffer[..n]
+            fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+                let $value = self.0;
+                Encoder::new(buffer).bytes(&$base).byte($code).finish()
+            }
+        }
+    };
 }
 
-pub fn build_change_connect_timeout_response<'a>(time: u32, buffer: &'a mut [u8]) -> &'a [u8] {
-    buffer.clone_from_slice(&CHANGE_CONNECT_TIMEOUT_RESPONSE);
-    let mut n = CHANGE_CONNECT_TIMEOUT_RESPONSE.len();
-    let time = time / 10;
+/// Implements [`Verify`] for a setter wrapping the very value `$query`
+/// answers with.
+macro_rules! verify_command {
+    ($command:ident, $query:ident, $setting:ident) => {
+        impl Verify for $command {
+            type Query = $query;
+
+            fn query(&self) -> $query {
+                $query
+            }
 
-    n += num2hex(time, &mut buffer[n..]);
+            fn expected(&self) -> Result<Setting, ParseError> {
+                Ok(Setting::$setting(self.0))
+            }
 
-    &buffer[..n]
+            fn actual(&self, response: <$query as AtCommand>::Response) -> Setting {
+                Setting::$setting(response)
+            }
+        }
+    };
 }
 
-pub const SET_CONNECT_UUID_BASE: [u8; 9] = *b"AT+LUUID=";
-pub const CONNECT_UUID_RESPONSE: [u8; 9] = *b"OK+LUUID=";
-pub const QUERY_CONNECT_UUID: [u8; 10]= *b"AT+LUUID=?";
-pub fn build_set_connect_uuid_command(uuid: UUID) -> [u8; 13] {
-    let mut result = [0; 13];
-    result.clone_from_slice(&SET_CONNECT_UUID_BASE);
+/// `AT`, answered with `OK` whenever the module is listening.
+#[derive(Debug, Clone, Copy)]
+pub struct Test;
+plain_command!(Test, OK_QUERY);
+
+/// `AT+DEFAULT`: restores the factory settings.
+#[derive(Debug, Clone, Copy)]
+pub struct ResetSettings;
+plain_command!(ResetSettings, RESET_SETTINGS_COMMAND);
+
+/// `AT+RESET`: restarts the module, keeping its settings.
+///
+/// The module answers `OK` and may print its boot banner right behind it.
+/// Lines after the `OK` are taken and dropped.
+#[derive(Debug, Clone, Copy)]
+pub struct Reboot;
+
+impl AtCommand for Reboot {
+    type Response = ();
+    type Partial = Option<()>;
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer).bytes(&REBOOT_COMMAND).finish()
+    }
+
+    fn decode(&self, partial: &mut Option<()>, line: Line<'_>) -> Result<(), ParseError> {
+        if partial.is_none() {
+            *partial = Some(ok(line)?);
+        }
 
-    let uuid: [u8; 4] = uuid.into();
-    result[SET_CONNECT_UUID_BASE.len()..].clone_from_slice(&uuid);
-    result
+        Ok(())
+    }
+
+    fn finish(&self, partial: Option<()>) -> Result<(), ParseError> {
+        partial.ok_or(ParseError::MissingValue)
+    }
 }
 
-pub fn build_set_connect_uuid_response(uuid: UUID) -> [u8; 13] {
-    let mut result = [0; 13];
-    result.clone_from_slice(&CONNECT_UUID_RESPONSE);
+/// `AT+CLEAR`: forgets the slave a master last connected to.
+#[derive(Debug, Clone, Copy)]
+pub struct ClearAddr;
+plain_command!(ClearAddr, CLEAR_ADDR);
+
+/// `AT+RX`: every setting at once.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryParameters;
+
+impl AtCommand for QueryParameters {
+    type Response = Parameters;
+    type Partial = ParametersParser;
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer).bytes(&QUERY_PARAMS_COMMAND).finish()
+    }
+
+    fn decode(&self, parser: &mut ParametersParser, line: Line<'_>) -> Result<(), ParseError> {
+        match line.token()? {
+            Token::Field { key, value } => parser.feed_field(key, value),
+            _ => Err(ParseError::PrefixError),
+        }
+    }
 
-    let uuid: [u8; 4] = uuid.into();
-    result[CONNECT_UUID_RESPONSE.len()..].clone_from_slice(&uuid);
-    result
+    fn finish(&self, parser: ParametersParser) -> Result<Parameters, ParseError> {
+        parser.finish()
+    }
 }
 
-pub const SET_SERVICE_UUID_BASE: [u8; 9] = *b"AT+SUUID=";
-pub const SERVICE_UUID_RESPONSE: [u8; 9] = *b"OK+SUUID=";
-pub const QUERY_SERVICE_UUID: [u8; 10]= *b"AT+SUUID=?";
-pub fn build_set_service_uuid_command(uuid: UUID) -> [u8; 13] {
-    let mut result = [0; 13];
-    result.clone_from_slice(&SET_SERVICE_UUID_BASE);
+/// `AT+VERSION`
+#[derive(Debug, Clone, Copy)]
+pub struct QueryVersion;
+query_command!(QueryVersion, QUERY_VERSION, Version);
+
+/// `AT+NAME=?`
+#[derive(Debug, Clone, Copy)]
+pub struct QueryName;
+query_command!(QueryName, QUERY_NAME, Name);
+
+/// `AT+AVDA=?`. The data is taken as raw bytes, so it need not be text,
+/// but a line break in it cuts the answer short.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryBroadcastData;
 
-    let uuid: [u8; 4] = uuid.into();
-    result[SET_SERVICE_UUID_BASE.len()..].clone_from_slice(&uuid);
-    result
+impl AtCommand for QueryBroadcastData {
+    const BARE_VALUE: bool = true;
+
+    single_line!(BroadcastData, |self, line| BroadcastData::try_from(
+        line.as_bytes()
+    ));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer).bytes(&QUERY_BROADCAST).finish()
+    }
 }
 
-pub fn build_set_service_uuid_response(uuid: UUID) -> [u8; 13] {
-    let mut result = [0; 13];
-    result.clone_from_slice(&SERVICE_UUID_RESPONSE);
+/// `AT+ROLE=?`
+#[derive(Debug, Clone, Copy)]
+pub struct QueryRole;
+query_command!(QueryRole, QUERY_ROLE, Role);
+
+/// `AT+BAUD=?`
+#[derive(Debug, Clone, Copy)]
+pub struct QuerySerialConfig;
+query_command!(QuerySerialConfig, QUERY_BAUD_RATE, SerialConfig);
 
-    let uuid: [u8; 4] = uuid.into();
-    result[SERVICE_UUID_RESPONSE.len()..].clone_from_slice(&uuid);
-    result
+/// `AT+ADDR=?`
+#[derive(Debug, Clone, Copy)]
+pub struct QueryAddr;
+query_command!(QueryAddr, QUERY_ADDR, Addr);
+
+/// `AT+RFPM=?`
+#[derive(Debug, Clone, Copy)]
+pub struct QueryRfPower;
+query_command!(QueryRfPower, QUERY_RF_POWER, RfPower);
+
+/// `AT+MODE=?`
+#[derive(Debug, Clone, Copy)]
+pub struct QueryPowerMode;
+query_command!(QueryPowerMode, QUERY_POWER_MODE, PowerMode);
+
+/// `AT+LED=?`
+#[derive(Debug, Clone, Copy)]
+pub struct QueryLedMode;
+query_command!(QueryLedMode, QUERY_LED_MODE, LedMode);
+
+/// `AT+CONT=?`
+#[derive(Debug, Clone, Copy)]
+pub struct QueryConnectable;
+
+impl AtCommand for QueryConnectable {
+    const BARE_VALUE: bool = true;
+
+    single_line!(IsConnectable, |self, line| IsConnectable::try_from(
+        line.as_bytes()
+    ));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer).bytes(&QUERY_CONNECTABLE).finish()
+    }
 }
 
-pub const SET_CHARACTERISTIC_UUID_BASE: [u8; 9] = *b"AT+TUUID=";
-pub const CHARACTERISTIC_UUID_RESPONSE: [u8; 9] = *b"OK+TUUID=";
-pub const QUERY_CHARACTERISTIC_UUID: [u8; 10]= *b"AT+TUUID=?";
-pub fn build_set_characteristic_uuid_command(uuid: UUID) -> [u8; 13] {
-    let mut result = [0; 13];
-    result.clone_from_slice(&SET_CHARACTERISTIC_UUID_BASE);
+/// `AT+NAME=`, taking the name as is; the module decides what it accepts.
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeName<'n>(pub &'n str);
 
-    let uuid: [u8; 4] = uuid.into();
-    result[SET_CHARACTERISTIC_UUID_BASE.len()..].clone_from_slice(&uuid);
-    result
+impl AtCommand for ChangeName<'_> {
+    single_line!((), |self, line| ok(line));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(&CHANGE_NAME_BASE)
+            .bytes(self.0.as_bytes())
+            .finish()
+    }
+}
+
+impl Verify for ChangeName<'_> {
+    type Query = QueryName;
+
+    fn query(&self) -> QueryName {
+        QueryName
+    }
+
+    fn expected(&self) -> Result<Setting, ParseError> {
+        Ok(Setting::Name(Name::try_from(self.0)?))
+    }
+
+    fn actual(&self, name: Name) -> Setting {
+        Setting::Name(name)
+    }
 }
 
-pub fn build_set_characteristic_uuid_response(uuid: UUID) -> [u8; 13] {
-    let mut result = [0; 13];
-    result.clone_from_slice(&CHARACTERISTIC_UUID_RESPONSE);
+/// `AT+AVDA=`: the data a broadcaster advertises.
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeBroadcastData<'d>(pub &'d [u8]);
 
-    let uuid: [u8; 4] = uuid.into();
-    result[CHARACTERISTIC_UUID_RESPONSE.len()..].clone_from_slice(&uuid);
-    result
+impl AtCommand for ChangeBroadcastData<'_> {
+    single_line!((), |self, line| ok(line));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(&CHANGE_BROADCAST_BASE)
+            .bytes(self.0)
+            .finish()
+    }
 }
 
-fn num2hex<'a>(mut num: u32, buffer: &'a mut [u8]) -> usize {
-    let n = 0;
-    while num > 0 {
-        buffer[n] = (num % 10) as u8;
+impl Verify for ChangeBroadcastData<'_> {
+    type Query = QueryBroadcastData;
+
+    fn query(&self) -> QueryBroadcastData {
+        QueryBroadcastData
+    }
+
+    fn expected(&self) -> Result<Setting, ParseError> {
+        Ok(Setting::BroadcastData(BroadcastData::try_from(self.0)?))
+    }
+
+    fn actual(&self, data: BroadcastData) -> Setting {
+        Setting::BroadcastData(data)
+    }
+}
+
+/// `AT+ROLE=`
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeRole(pub Role);
+code_command!(ChangeRole, ROLE_BASE, |role| match role {
+    Role::Master => b'M',
+    Role::Slave => b'S',
+});
+verify_command!(ChangeRole, QueryRole, Role);
+
+/// `AT+CONT=`
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeConnectable(pub IsConnectable);
+code_command!(ChangeConnectable, CONNECTABLE_BASE, |c| if c.0 {
+    b'0'
+} else {
+    b'1'
+});
+verify_command!(ChangeConnectable, QueryConnectable, Connectable);
+
+/// `AT+RFPM=`
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeRfPower(pub RfPower);
+code_command!(ChangeRfPower, RF_POWER_BASE, |power| power.code());
+verify_command!(ChangeRfPower, QueryRfPower, RfPower);
+
+/// `AT+MODE=`
+#[derive(Debug, Clone, Copy)]
+pub struct ChangePowerMode(pub PowerMode);
+code_command!(ChangePowerMode, POWER_MODE_BASE, |mode| mode.code());
+verify_command!(ChangePowerMode, QueryPowerMode, PowerMode);
+
+/// `AT+LED=`
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeLedMode(pub LedMode);
+code_command!(ChangeLedMode, LED_MODE_BASE, |mode| mode.code());
+verify_command!(ChangeLedMode, QueryLedMode, LedMode);
+
+/// `AT+AUST=`, in seconds. See [`AUTO_SLEEP_RANGE_S`]; times outside it
+/// fail to encode with [`ParseError::OutOfRange`].
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeAutoSleep(pub u32);
+
+impl AtCommand for ChangeAutoSleep {
+    single_line!((), |self, line| ok(line));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(&AUTO_SLEEP_BASE)
+            .num(checked(self.0, &AUTO_SLEEP_RANGE_S)?)
+            .finish()
+    }
+}
+
+/// `AT+ADDR=`
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeAddr(pub Addr);
+
+impl AtCommand for ChangeAddr {
+    single_line!((), |self, line| ok(line));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(&CHANGE_ADDR_BASE)
+            .bytes(&self.0.to_hex())
+            .finish()
+    }
+}
+
+verify_command!(ChangeAddr, QueryAddr, Addr);
+
+/// `AT+BAUD=`. The module switches right after answering.
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeSerialConfig(pub SerialConfig);
+
+impl AtCommand for ChangeSerialConfig {
+    single_line!((), |self, line| ok(line));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(&CHANGE_BAUD_RATE_BASE)
+            .num(self.0.baud as u32)
+            .byte(b',')
+            .byte(self.0.parity.code())
+            .finish()
+    }
+}
+
+verify_command!(ChangeSerialConfig, QuerySerialConfig, SerialConfig);
+
+/// `AT+CINT=`, in milliseconds. See [`CONNECT_INTERNAL_RANGE_MS`]; bounds
+/// outside it, or `min` above `max`, fail to encode with
+/// [`ParseError::OutOfRange`].
+///
+/// The module echoes both bounds back, which is checked.
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeConnectInternal {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl ChangeConnectInternal {
+    /// Both bounds in the module's units.
+    fn units(&self) -> Result<(u32, u32), ParseError> {
+        if self.min > self.max {
+            return Err(ParseError::OutOfRange);
+        }
+
+        Ok((
+            connect_internal_units(self.min)?,
+            connect_internal_units(self.max)?,
+        ))
+    }
+}
+
+impl AtCommand for ChangeConnectInternal {
+    single_line!((), |self, line| {
+        let (min, max) = self.units()?;
+        let mut buffer = [0; 24];
+        let expected = Encoder::new(&mut buffer)
+            .num(min)
+            .byte(b',')
+            .num(max)
+            .finish()?;
+        echo(line, "CINT", expected)
+    });
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        let (min, max) = self.units()?;
+        let encoder = Encoder::new(buffer)
+            .bytes(&CHANGE_CONNECT_INTERNAL_BASE)
+            .num(min);
+        if min == max {
+            encoder.finish()
+        } else {
+            encoder.byte(b',').num(max).finish()
+        }
+    }
+}
+
+/// `AT+CTOUT=`, in milliseconds. See [`CONNECT_TIMEOUT_RANGE_MS`]; timeouts
+/// outside it fail to encode with [`ParseError::OutOfRange`].
+///
+/// The module echoes the timeout back, which is checked.
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeConnectTimeout(pub u32);
+
+impl ChangeConnectTimeout {
+    /// The timeout in the module's 10 ms units.
+    fn units(&self) -> Result<u32, ParseError> {
+        Ok(checked(self.0, &CONNECT_TIMEOUT_RANGE_MS)? / 10)
+    }
+}
+
+impl AtCommand for ChangeConnectTimeout {
+    single_line!((), |self, line| {
+        let mut buffer = [0; 10];
+        echo(
+            line,
+            "CTOUT",
+            Encoder::new(&mut buffer).num(self.units()?).finish()?,
+        )
+    });
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(&CHANGE_CONNECT_TIMEOUT_BASE)
+            .num(self.units()?)
+            .finish()
+    }
+}
+
+/// `AT+AINT=?`, answered in milliseconds, rounded down.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryAdvertisingInterval;
+
+impl AtCommand for QueryAdvertisingInterval {
+    single_line!(u32, |self, line| Ok::<_, ParseError>(
+        advertising_interval_ms(reply(line, "AINT")?.parse()?)
+    ));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(&QUERY_ADVERTISING_INTERVAL)
+            .finish()
+    }
+}
+
+/// `AT+AINT=`, in milliseconds. See [`ADVERTISING_INTERVAL_RANGE_MS`];
+/// intervals outside it fail to encode with [`ParseError::OutOfRange`].
+///
+/// The module echoes the interval back, which is checked.
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeAdvertisingInterval(pub u32);
+
+impl AtCommand for ChangeAdvertisingInterval {
+    single_line!((), |self, line| {
+        let mut buffer = [0; 10];
+        let units = advertising_interval_units(self.0)?;
+        echo(line, "AINT", Encoder::new(&mut buffer).num(units).finish()?)
+    });
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(&ADVERTISING_INTERVAL_BASE)
+            .num(advertising_interval_units(self.0)?)
+            .finish()
+    }
+}
+
+impl Verify for ChangeAdvertisingInterval {
+    type Query = QueryAdvertisingInterval;
+
+    fn query(&self) -> QueryAdvertisingInterval {
+        QueryAdvertisingInterval
+    }
+
+    fn expected(&self) -> Result<Setting, ParseError> {
+        Ok(Setting::AdvertisingInterval(advertising_interval_ms(
+            advertising_interval_units(self.0)?,
+        )))
+    }
+
+    fn actual(&self, ms: u32) -> Setting {
+        Setting::AdvertisingInterval(ms)
+    }
+}
+
+/// The three UUIDs the module keeps.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum UuidSlot {
+    /// `LUUID`: the service a master looks for when connecting.
+    Connect,
+    /// `SUUID`: the service a slave offers.
+    Service,
+    /// `TUUID`: the characteristic carrying the serial data.
+    Characteristic,
+}
+
+impl UuidSlot {
+    /// The name the module's commands and answers use for the slot.
+    pub fn key(self) -> &'static str {
+        match self {
+            UuidSlot::Connect => "LUUID",
+            UuidSlot::Service => "SUUID",
+            UuidSlot::Characteristic => "TUUID",
+        }
+    }
+}
+
+/// `AT+LUUID=?`, `AT+SUUID=?` or `AT+TUUID=?`, answered as `OK+SUUID=FFE0`.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryUuid(pub UuidSlot);
+
+impl AtCommand for QueryUuid {
+    single_line!(UUID, |self, line| UUID::try_from(
+        reply(line, self.0.key())?.as_bytes()
+    ));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(b"AT+")
+            .bytes(self.0.key().as_bytes())
+            .bytes(b"=?")
+            .finish()
+    }
+}
+
+/// `AT+LUUID=`, `AT+SUUID=` or `AT+TUUID=`.
+///
+/// The module echoes the UUID back, which is checked.
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeUuid(pub UuidSlot, pub UUID);
+
+impl AtCommand for ChangeUuid {
+    single_line!((), |self, line| echo(
+        line,
+        self.0.key(),
+        &<[u8; 4]>::from(self.1)
+    ));
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        Encoder::new(buffer)
+            .bytes(b"AT+")
+            .bytes(self.0.key().as_bytes())
+            .byte(b'=')
+            .bytes(&<[u8; 4]>::from(self.1))
+            .finish()
+    }
+}
+
+impl Verify for ChangeUuid {
+    type Query = QueryUuid;
+
+    fn query(&self) -> QueryUuid {
+        QueryUuid(self.0)
+    }
+
+    fn expected(&self) -> Result<Setting, ParseError> {
+        Ok(Setting::Uuid(self.0, self.1))
+    }
+
+    fn actual(&self, uuid: UUID) -> Setting {
+        Setting::Uuid(self.0, uuid)
+    }
+}
+
+/// Writes `num` as ASCII decimal digits and returns how many were written.
+fn num2dec(mut num: u32, buffer: &mut [u8]) -> usize {
+    let mut n = 0;
+    loop {
+        buffer[n] = b'0' + (num % 10) as u8;
+        n += 1;
         num /= 10;
+        if num == 0 {
+            break;
+        }
     }
 
     buffer[..n].reverse();
     n
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::baudrate::{BaudRate, Parity};
+    use crate::response::ResponseReader;
+    use crate::Error;
+
+    /// Decodes `answer` the way the driver does once the module goes quiet.
+    fn decode<A: AtCommand>(command: A, answer: &[u8]) -> Result<A::Response, Error<()>> {
+        let mut reader = ResponseReader::new(&command);
+        for &byte in answer {
+            reader.push(byte)?;
+        }
+        reader.finish()
+    }
+
+    macro_rules! assert_encodes {
+        ($command:expr, $expected:expr) => {
+            let mut buffer = [0; 32];
+            assert_eq!($command.encode(&mut buffer).unwrap(), &$expected[..]);
+        };
+    }
+
+    #[test]
+    fn num2dec_writes_ascii_digits() {
+        let mut buffer = [0; 10];
+        assert_eq!(num2dec(0, &mut buffer), 1);
+        assert_eq!(&buffer[..1], b"0");
+        assert_eq!(num2dec(3200, &mut buffer), 4);
+        assert_eq!(&buffer[..4], b"3200");
+        assert_eq!(num2dec(u32::MAX, &mut buffer), 10);
+        assert_eq!(&buffer[..10], b"4294967295");
+    }
+
+    #[test]
+    fn encoding_into_a_short_buffer_is_an_error() {
+        let mut buffer = [0; 8];
+        assert!(matches!(
+            ChangeName("sensor-1").encode(&mut buffer),
+            Err(ParseError::TooLong)
+        ));
+        assert_eq!(Test.encode(&mut buffer).unwrap(), b"AT");
+    }
+
+    #[test]
+    fn plain_commands_expect_ok() {
+        assert!(decode(Test, b"OK").is_ok());
+        assert!(decode(ResetSettings, b"OK\r\n").is_ok());
+        assert!(decode(Reboot, b"ERROR").is_err());
+        assert!(decode(ClearAddr, b"").is_err());
+    }
+
+    #[test]
+    fn reboot_drops_the_banner_behind_ok() {
+        assert!(decode(Reboot, b"OK").is_ok());
+        assert!(decode(Reboot, b"OK\r\nHC-08 V3.1,2017-07-07\r\n").is_ok());
+        assert!(decode(Reboot, b"OK\r\nHC-08 V3.1,20").is_ok());
+        assert!(decode(Reboot, b"HC-08 V3.1,2017-07-07\r\nOK\r\n").is_err());
+    }
+
+    #[test]
+    fn answers_are_decoded_line_by_line() {
+        assert!(matches!(
+            decode(ChangeRole(Role::Slave), b"ERROR"),
+            Err(Error::Rejected)
+        ));
+        assert!(decode(Test, b"OK\r\nOK").is_err());
+        assert!(decode(QueryRole, b"").is_err());
+
+        let params = decode(
+            QueryParameters,
+            b"Name:HC-08\r\nRole:Slave\r\nBaud:9600,NONE\r\nAddr:48,87,2D,12,34,56\r\n",
+        )
+        .unwrap();
+        assert_eq!(params.role, Role::Slave);
+        assert!(decode(QueryParameters, b"Name:HC-08\r\nOK").is_err());
+    }
+
+    #[test]
+    fn bare_values_are_taken_as_they_arrive() {
+        for (answer, name) in [
+            (&b"OK"[..], "OK"),
+            (b"ERROR-1", "ERROR-1"),
+            (b"Key:value\r\n", "Key:value"),
+        ] {
+            assert_eq!(decode(QueryName, answer).unwrap().as_str(), name);
+        }
+        assert!(matches!(
+            decode(ChangeName("OK"), b"ERROR"),
+            Err(Error::Rejected)
+        ));
+        for answer in [&b"ERROR"[..], b"ERROR\r\n"] {
+            assert!(matches!(decode(QueryName, answer), Err(Error::Rejected)));
+        }
+    }
+
+    #[test]
+    fn baud_rate_command() {
+        let config = SerialConfig {
+            baud: BaudRate::Bps115200,
+            parity: Parity::None,
+        };
+        assert_encodes!(ChangeSerialConfig(config), b"AT+BAUD=115200,N");
+
+        let config = SerialConfig {
+            baud: BaudRate::Bps1200,
+            parity: Parity::Odd,
+        };
+        assert_encodes!(ChangeSerialConfig(config), b"AT+BAUD=1200,O");
+        assert_eq!(decode(QuerySerialConfig, b"1200,ODD").unwrap(), config);
+    }
+
+    #[test]
+    fn connect_internal_command() {
+        let command = |min, max| ChangeConnectInternal { min, max };
+        assert_encodes!(command(20, 40), b"AT+CINT=16,32");
+        assert_encodes!(command(100, 100), b"AT+CINT=80");
+        assert_encodes!(command(8, 4000), b"AT+CINT=6,3200");
+        assert!(decode(command(100, 100), b"OK+CINT=80,80").is_ok());
+        assert!(decode(command(100, 100), b"OK+CINT=80,81").is_err());
+
+        let mut buffer = [0; 32];
+        for (min, max) in [(u32::MAX, u32::MAX), (4, 40), (40, 20)] {
+            assert!(matches!(
+                command(min, max).encode(&mut buffer),
+                Err(ParseError::OutOfRange)
+            ));
+        }
+    }
+
+    #[test]
+    fn connect_timeout_command() {
+        assert_encodes!(ChangeConnectTimeout(32000), b"AT+CTOUT=3200");
+        assert!(decode(ChangeConnectTimeout(100), b"OK+CTOUT=10").is_ok());
+        assert!(decode(ChangeConnectTimeout(100), b"OK").is_err());
+
+        let mut buffer = [0; 32];
+        for ms in [0, 5, 99, 32001] {
+            assert!(matches!(
+                ChangeConnectTimeout(ms).encode(&mut buffer),
+                Err(ParseError::OutOfRange)
+            ));
+        }
+    }
+
+    #[test]
+    fn uuid_commands_name_their_slot() {
+        let uuid = UUID(0xFFFF);
+        assert_encodes!(QueryUuid(UuidSlot::Connect), b"AT+LUUID=?");
+        assert_encodes!(ChangeUuid(UuidSlot::Service, uuid), b"AT+SUUID=FFFF");
+        assert_encodes!(ChangeUuid(UuidSlot::Characteristic, uuid), b"AT+TUUID=FFFF");
+    }
+
+    #[test]
+    fn uuid_commands_write_the_uuid_in_order() {
+        assert_encodes!(
+            ChangeUuid(UuidSlot::Service, UUID(0xFFE0)),
+            b"AT+SUUID=FFE0"
+        );
+        let command = ChangeUuid(UuidSlot::Connect, UUID(0x1234));
+        assert!(decode(command, b"OK+LUUID=1234").is_ok());
+        assert!(decode(command, b"OK+SUUID=1234").is_err());
+    }
+
+    #[test]
+    fn uuid_query_checks_the_slot() {
+        let query = QueryUuid(UuidSlot::Service);
+        assert_eq!(decode(query, b"OK+SUUID=FFE0\r\n").unwrap(), UUID(0xFFE0));
+        assert!(decode(query, b"OK+TUUID=FFE0").is_err());
+        assert!(decode(query, b"OK+SUUID=").is_err());
+    }
+
+    #[test]
+    fn addr_command() {
+        let addr = Addr::new([0x48, 0x87, 0x2D, 0xAB, 0xCD, 0xEF]);
+        assert_encodes!(ChangeAddr(addr), b"AT+ADDR=48872DABCDEF");
+        assert_eq!(decode(QueryAddr, b"48872DABCDEF").unwrap(), addr);
+    }
+
+    #[test]
+    fn rf_power_command() {
+        assert_encodes!(ChangeRfPower(RfPower::Dbm4), b"AT+RFPM=0");
+        assert_encodes!(ChangeRfPower(RfPower::DbmMinus23), b"AT+RFPM=3");
+        assert_eq!(
+            decode(QueryRfPower, b"-23dBm").unwrap(),
+            RfPower::DbmMinus23
+        );
+    }
+
+    #[test]
+    fn power_commands() {
+        assert_encodes!(ChangePowerMode(PowerMode::LowPower2), b"AT+MODE=2");
+        assert_encodes!(ChangeAutoSleep(300), b"AT+AUST=300");
+
+        let mut buffer = [0; 32];
+        for seconds in [0, 301] {
+            assert!(matches!(
+                ChangeAutoSleep(seconds).encode(&mut buffer),
+                Err(ParseError::OutOfRange)
+            ));
+        }
+    }
+
+    #[test]
+    fn advertising_interval_command() {
+        assert_encodes!(ChangeAdvertisingInterval(20), b"AT+AINT=32");
+        assert_encodes!(ChangeAdvertisingInterval(10240), b"AT+AINT=16384");
+        assert!(decode(ChangeAdvertisingInterval(100), b"OK+AINT=160").is_ok());
+        assert_eq!(
+            decode(QueryAdvertisingInterval, b"OK+AINT=160").unwrap(),
+            100
+        );
+        assert!(decode(QueryAdvertisingInterval, b"OK+AINT=900000000").is_ok());
+        assert!(decode(QueryAdvertisingInterval, b"OK+AINT=99999999999").is_err());
+
+        let mut buffer = [0; 32];
+        for ms in [0, 19, 10241, u32::MAX] {
+            assert!(matches!(
+                ChangeAdvertisingInterval(ms).encode(&mut buffer),
+                Err(ParseError::OutOfRange)
+            ));
+        }
+    }
+
+    #[test]
+    fn advertising_interval_units_round_trip() {
+        for ms in [20, 100, 625, 1000, 10240] {
+            assert_eq!(
+                advertising_interval_ms(advertising_interval_units(ms).unwrap()),
+                ms
+            );
+        }
+        assert_eq!(advertising_interval_ms(33), 20);
+        assert_eq!(advertising_interval_ms(u32::MAX), 2_684_354_559);
+        assert!(advertising_interval_units(u32::MAX).is_err());
+    }
+
+    #[test]
+    fn verify_expects_what_the_query_reports() {
+        let command = ChangeAdvertisingInterval(101);
+        assert_eq!(
+            command.expected().unwrap(),
+            command.actual(decode(command.query(), b"OK+AINT=161").unwrap())
+        );
+
+        let command = ChangeUuid(UuidSlot::Service, UUID(0xFFE0));
+        assert_encodes!(command.query(), b"AT+SUUID=?");
+        assert_ne!(command.expected().unwrap(), command.actual(UUID(0xFFE1)));
+
+        let command = ChangeBroadcastData(b"\x01\xff");
+        assert_encodes!(command.query(), b"AT+AVDA=?");
+        assert_eq!(
+            command.expected().unwrap(),
+            command.actual(decode(command.query(), b"\x01\xff\r\n").unwrap())
+        );
+        assert!(matches!(
+            ChangeName("a-name-well-over-twenty-bytes").expected(),
+            Err(ParseError::TooLong)
+        ));
+    }
+
+    #[test]
+    fn led_mode_command() {
+        assert_encodes!(ChangeLedMode(LedMode::Off), b"AT+LED=0");
+        assert_encodes!(ChangeLedMode(LedMode::On), b"AT+LED=1");
+    }
+
+    #[test]
+    fn mode_commands() {
+        assert_encodes!(ChangeRole(Role::Slave), b"AT+ROLE=S");
+        assert_encodes!(ChangeConnectable(IsConnectable(false)), b"AT+CONT=1");
+        assert_eq!(decode(QueryRole, b"Master").unwrap(), Role::Master);
+        assert_eq!(
+            decode(QueryConnectable, b"Non-Connectable\r\n").unwrap(),
+            IsConnectable(false)
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Configuring a module from one description of the settings wanted.
+//!
+//! [`Hc08Config`] lists the settings to enforce, each optional, and
+//! [`Hc08::apply`] brings a module in line with it, sending only the
+//! commands for settings that differ.
+
+use crate::any::{AnyHc08, Mode};
+use crate::command::{
+    AtCommand, ChangeAdvertisingInterval, ChangeAutoSleep, ChangeConnectInternal,
+    ChangeConnectTimeout, ChangeConnectable, ChangeLedMode, ChangeName, ChangePowerMode,
+    ChangeRfPower, ChangeRole, ChangeUuid, UuidSlot, Verify,
+};
+use crate::hal::{Delay, Serial};
+use crate::parameters::connectable::IsConnectable;
+use crate::parameters::led_mode::LedMode;
+use crate::parameters::power_mode::PowerMode;
+use crate::parameters::rf_power::RfPower;
+use crate::parameters::role::Role;
+use crate::parameters::uuid::UUID;
+use crate::parameters::ParseError;
+use crate::{Error, Hc08, Hc08Error, COMMAND_LEN};
+
+/// Settings to bring a module to; `None` leaves a setting as it is.
+///
+/// The same type reports what [`Hc08::apply`] changed.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Hc08Config<'a> {
+    pub name: Option<&'a str>,
+    pub role: Option<Role>,
+    pub connectable: Option<IsConnectable>,
+    /// Central mode only.
+    pub connect_uuid: Option<UUID>,
+    /// Peripheral mode only.
+    pub service_uuid: Option<UUID>,
+    /// Peripheral mode only.
+    pub characteristic_uuid: Option<UUID>,
+    /// In milliseconds, slaves only. See
+    /// [`ADVERTISING_INTERVAL_RANGE_MS`](crate::command::ADVERTISING_INTERVAL_RANGE_MS).
+    pub advertising_interval_ms: Option<u32>,
+    /// `(min, max)` in milliseconds, connectable modes only. See
+    /// [`CONNECT_INTERNAL_RANGE_MS`](crate::command::CONNECT_INTERNAL_RANGE_MS).
+    pub connect_internal_ms: Option<(u32, u32)>,
+    /// In milliseconds, connectable modes only. See
+    /// [`CONNECT_TIMEOUT_RANGE_MS`](crate::command::CONNECT_TIMEOUT_RANGE_MS).
+    pub connect_timeout_ms: Option<u32>,
+    pub rf_power: Option<RfPower>,
+    pub power_mode: Option<PowerMode>,
+    /// In seconds. See
+    /// [`AUTO_SLEEP_RANGE_S`](crate::command::AUTO_SLEEP_RANGE_S).
+    pub auto_sleep_s: Option<u32>,
+    pub led_mode: Option<LedMode>,
+}
+
+impl Hc08Config<'_> {
+    /// Checks every setting is allowed in `mode` and within range, so a bad
+    /// configuration is refused before anything is sent.
+    fn check<E, W>(&self, mode: Mode) -> Result<(), Error<E, W>> {
+        let slave = mode.role() == Role::Slave;
+        let connectable = mode.connectable().0;
+        if (self.connect_uuid.is_some() && mode != Mode::Central)
+            || ((self.service_uuid.is_some() || self.characteristic_uuid.is_some())
+                && mode != Mode::Peripheral)
+            || (self.advertising_interval_ms.is_some() && !slave)
+            || ((self.connect_internal_ms.is_some() || self.connect_timeout_ms.is_some())
+                && !connectable)
+        {
+            return Err(Error::WrongMode);
+        }
+
+        if let Some(ms) = self.advertising_interval_ms {
+            encodes(&ChangeAdvertisingInterval(ms))?;
+        }
+        if let Some((min, max)) = self.connect_internal_ms {
+            encodes(&ChangeConnectInternal { min, max })?;
+        }
+        if let Some(ms) = self.connect_timeout_ms {
+            encodes(&ChangeConnectTimeout(ms))?;
+        }
+        if let Some(seconds) = self.auto_sleep_s {
+            encodes(&ChangeAutoSleep(seconds))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Encodes `command` without sending it, which fails just as sending it
+/// would for an argument out of range.
+fn encodes(command: &impl AtCommand) -> Result<(), ParseError> {
+    command.encode(&mut [0; COMMAND_LEN])?;
+    Ok(())
+}
+
+/// The handle in the mode the module ended up in and what was changed, or
+/// the error and the handle in the mode the module was left in.
+pub(crate) type Applied<'c, S, D> =
+    Result<(AnyHc08<S, D>, Hc08Config<'c>), (Hc08Error<S>, AnyHc08<S, D>)>;
+
+/// What is known of the module's mode while [`Hc08::apply`] runs.
+#[derive(Debug, Clone, Copy)]
+enum Reached {
+    /// Not read yet and not changed: the mode of the handle.
+    Unchanged,
+    /// Reported by the module, or set by a change it acknowledged.
+    Known(Mode),
+    /// A role or connectability change went out without an answer, so the
+    /// module may or may not have left the mode it was last known in.
+    Unsure(Mode),
+}
+
+impl<S, D, R, C> Hc08<S, D, R, C>
+where
+    S: Serial,
+    D: Delay,
+{
+    /// Brings the module in line with `config`.
+    ///
+    /// The current settings are read with `AT+RX`, plus a query each for
+    /// the advertising interval and power mode, which it does not report.
+    /// Only settings that differ are sent: role and connectability first,
+    /// since they decide which of the rest apply, then name, UUIDs,
+    /// intervals and power. The connection interval and timeout and the
+    /// auto-sleep time cannot be read back, so they are always sent when
+    /// given.
+    ///
+    /// Returns the handle for the resulting mode, with a configuration
+    /// holding just the settings that were changed. Settings that do not
+    /// fit the resulting mode fail with [`Error::WrongMode`] and nothing is
+    /// sent. On any other error the module may be partly configured.
+    ///
+    /// On failure the handle comes back for the mode the module was left
+    /// in. If a role or connectability change went unanswered the mode is
+    /// queried; should the module not answer that either, the handle is
+    /// for the mode it was last known to be in.
+    pub fn apply<'c>(mut self, config: &Hc08Config<'c>) -> Applied<'c, S, D>
+    where
+        AnyHc08<S, D>: From<Self>,
+    {
+        let mut reached = Reached::Unchanged;
+        match self.apply_settings(config, &mut reached) {
+            Ok((mode, changed)) => Ok((self.into_any_as(mode), changed)),
+            Err(e) => {
+                let hc08 = match reached {
+                    Reached::Unchanged => AnyHc08::from(self),
+                    Reached::Known(mode) => self.into_any_as(mode),
+                    Reached::Unsure(last) => {
+                        let mode = self.query_mode().unwrap_or(last);
+                        self.into_any_as(mode)
+                    }
+                };
+                Err((e, hc08))
+            }
+        }
+    }
+
+    /// The work of [`apply`](Self::apply), returning the resulting mode.
+    /// Keeps `reached` up to date as the role and connectability change.
+    fn apply_settings<'c>(
+        &mut self,
+        config: &Hc08Config<'c>,
+        reached: &mut Reached,
+    ) -> Result<(Mode, Hc08Config<'c>), Hc08Error<S>> {
+        let params = self.get_parameters()?;
+        let connectable = match params.connectable {
+            Some(connectable) => connectable,
+            None => self.query_connectable()?,
+        };
+        let current = Mode::new(params.role, connectable);
+        *reached = Reached::Known(current);
+
+        let mode = Mode::new(
+            config.role.unwrap_or(params.role),
+            config.connectable.unwrap_or(connectable),
+        );
+        config.check(mode)?;
+
+        let mut changed = Hc08Config::default();
+        if let Some(role) = config.role.filter(|&role| role != params.role) {
+            *reached = Reached::Unsure(current);
+            self.change(&ChangeRole(role))?;
+            self.delay.delay_ms(200);
+            *reached = Reached::Known(Mode::new(role, connectable));
+            changed.role = Some(role);
+        }
+        if let Some(c) = config.connectable.filter(|&c| c != connectable) {
+            *reached = Reached::Unsure(Mode::new(mode.role(), connectable));
+            self.change(&ChangeConnectable(c))?;
+            self.delay.delay_ms(200);
+            *reached = Reached::Known(mode);
+            changed.connectable = Some(c);
+        }
+        if changed.role.is_some() || changed.connectable.is_some() {
+            let actual = self.query_mode()?;
+            *reached = Reached::Known(actual);
+            if actual != mode {
+                return Err(Error::WrongResponse);
+            }
+        }
+
+        if let Some(name) = config.name.filter(|&name| name != params.name.as_str()) {
+            self.change(&ChangeName(name))?;
+            changed.name = Some(name);
+        }
+
+        let uuids = [
+            (UuidSlot::Connect, config.connect_uuid, params.connect_uuid),
+            (UuidSlot::Service, config.service_uuid, params.service_uuid),
+            (
+                UuidSlot::Characteristic,
+                config.characteristic_uuid,
+                params.characteristic_uuid,
+            ),
+        ];
+        for (slot, wanted, current) in uuids {
+            if let Some(uuid) = wanted.filter(|&uuid| Some(uuid) != current) {
+                self.change(&ChangeUuid(slot, uuid))?;
+                match slot {
+                    UuidSlot::Connect => changed.connect_uuid = Some(uuid),
+                    UuidSlot::Service => changed.service_uuid = Some(uuid),
+                    UuidSlot::Characteristic => changed.characteristic_uuid = Some(uuid),
+                }
+            }
+        }
+
+        if let Some(ms) = config.advertising_interval_ms {
+            if self.change_if_differs(&ChangeAdvertisingInterval(ms))? {
+                changed.advertising_interval_ms = Some(ms);
+            }
+        }
+        if let Some((min, max)) = config.connect_internal_ms {
+            self.execute(&ChangeConnectInternal { min, max })?;
+            changed.connect_internal_ms = Some((min, max));
+        }
+        if let Some(ms) = config.connect_timeout_ms {
+            self.execute(&ChangeConnectTimeout(ms))?;
+            changed.connect_timeout_ms = Some(ms);
+        }
+
+        if let Some(power) = config.rf_power.filter(|&p| Some(p) != params.rf_power) {
+            self.change(&ChangeRfPower(power))?;
+            changed.rf_power = Some(power);
+        }
+        if let Some(led) = config.led_mode.filter(|&led| Some(led) != params.led_mode) {
+            self.change(&ChangeLedMode(led))?;
+            changed.led_mode = Some(led);
+        }
+        if let Some(seconds) = config.auto_sleep_s {
+            self.execute(&ChangeAutoSleep(seconds))?;
+            changed.auto_sleep_s = Some(seconds);
+        }
+        // Last, since a low power mode may put the module to sleep.
+        if let Some(power_mode) = config.power_mode {
+            if self.change_if_differs(&ChangePowerMode(power_mode))? {
+                changed.power_mode = Some(power_mode);
+            }
+        }
+
+        Ok((mode, changed))
+    }
+
+    /// Runs `command` unless the module already reports what it would set.
+    /// Returns whether it was run.
+    fn change_if_differs<A: Verify>(&mut self, command: &A) -> Result<bool, Hc08Error<S>> {
+        let current = command.actual(self.execute(&command.query())?);
+        if current == command.expected()? {
+            return Ok(false);
+        }
+
+        self.change(command)?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,217 @@
+//! Serial and delay traits the driver is written against.
+//!
+//! With the `eh0` feature (on by default) every embedded-hal 0.2 serial port
+//! implementing `Read<u8> + Write<u8>` and every `DelayMs<u32>` can be handed
+//! to [`Hc08`](crate::Hc08) directly; a delay that also implements
+//! `DelayUs<u32>` can be wrapped in [`UsDelay`] to poll the port more often.
+//! With the `eh1` feature, embedded-io ports and embedded-hal 1.0 delays are
+//! used through [`IoSerial`] and [`NsDelay`].
+
+use crate::parameters::baudrate::{BaudRate, Parity};
+use crate::POLL_INTERVAL_US;
+
+/// A byte-oriented, non-blocking serial port.
+///
+/// Receiving and transmitting may fail with different errors, as they do
+/// on many embedded-hal 0.2 ports whose writes cannot fail at all.
+pub trait Serial {
+    type ReadError;
+    type WriteError;
+
+    /// Reads a single byte, returning `WouldBlock` if none is available yet.
+    fn read_byte(&mut self) -> nb::Result<u8, Self::ReadError>;
+
+    /// Writes a single byte, returning `WouldBlock` if the port is busy.
+    fn write_byte(&mut self, byte: u8) -> nb::Result<(), Self::WriteError>;
+}
+
+/// A serial port whose line settings can be changed at runtime.
+///
+/// [`Hc08::change_baud_rate`](crate::Hc08::change_baud_rate) calls this once
+/// the module has accepted the new settings, so the host follows it. It
+/// fails with the port's receive error, which is where line setting errors
+/// such as a bad baud rate show up.
+pub trait Reconfigure: Serial {
+    fn reconfigure(&mut self, baud_rate: BaudRate, parity: Parity) -> Result<(), Self::ReadError>;
+}
+
+/// A delay provider.
+pub trait Delay {
+    fn delay_ms(&mut self, ms: u32);
+
+    /// Waits `us` microseconds. Unless overridden this rounds up to whole
+    /// milliseconds.
+    fn delay_us(&mut self, us: u32) {
+        self.delay_ms(us.div_ceil(1000));
+    }
+
+    /// How long to wait between polls of the serial port, in microseconds.
+    /// A millisecond unless overridden, the shortest wait `delay_ms` allows;
+    /// at high baud rates that may overrun a UART without a receive FIFO, so
+    /// delays overriding [`delay_us`](Self::delay_us) should return
+    /// [`POLL_INTERVAL_US`].
+    fn poll_interval_us(&self) -> u32 {
+        1000
+    }
+}
+
+#[cfg(feature = "eh0")]
+impl<S> Serial for S
+where
+    S: embedded_hal::serial::Read<u8> + embedded_hal::serial::Write<u8>,
+{
+    type ReadError = <S as embedded_hal::serial::Read<u8>>::Error;
+    type WriteError = <S as embedded_hal::serial::Write<u8>>::Error;
+
+    fn read_byte(&mut self) -> nb::Result<u8, Self::ReadError> {
+        embedded_hal::serial::Read::read(self)
+    }
+
+    fn write_byte(&mut self, byte: u8) -> nb::Result<(), Self::WriteError> {
+        embedded_hal::serial::Write::write(self, byte)
+    }
+}
+
+#[cfg(feature = "eh0")]
+impl<D> Delay for D
+where
+    D: embedded_hal::blocking::delay::DelayMs<u32>,
+{
+    fn delay_ms(&mut self, ms: u32) {
+        embedded_hal::blocking::delay::DelayMs::delay_ms(self, ms)
+    }
+}
+
+/// Adapts an embedded-hal 0.2 delay with microsecond resolution to
+/// [`Delay`], so the serial port is polled every [`POLL_INTERVAL_US`]
+/// instead of every millisecond.
+#[cfg(feature = "eh0")]
+#[derive(Debug)]
+pub struct UsDelay<D>(D);
+
+#[cfg(feature = "eh0")]
+impl<D> UsDelay<D> {
+    pub fn new(inner: D) -> Self {
+        Self(inner)
+    }
+
+    pub fn into_inner(self) -> D {
+        self.0
+    }
+}
+
+#[cfg(feature = "eh0")]
+impl<D> Delay for UsDelay<D>
+where
+    D: embedded_hal::blocking::delay::DelayMs<u32> + embedded_hal::blocking::delay::DelayUs<u32>,
+{
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms)
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us)
+    }
+
+    fn poll_interval_us(&self) -> u32 {
+        POLL_INTERVAL_US
+    }
+}
+
+/// Adapts an embedded-io port to [`Serial`].
+///
+/// `ReadReady` is used to poll for incoming bytes, so reads never block.
+#[cfg(feature = "eh1")]
+#[derive(Debug)]
+pub struct IoSerial<T>(T);
+
+#[cfg(feature = "eh1")]
+impl<T> IoSerial<T> {
+    pub fn new(inner: T) -> Self {
+        Self(inner)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<T> Serial for IoSerial<T>
+where
+    T: embedded_io::Read + embedded_io::Write + embedded_io::ReadReady,
+{
+    type ReadError = T::Error;
+    type WriteError = T::Error;
+
+    fn read_byte(&mut self) -> nb::Result<u8, T::Error> {
+        if !self.0.read_ready()? {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let mut byte = [0u8; 1];
+        match self.0.read(&mut byte)? {
+            0 => Err(nb::Error::WouldBlock),
+            _ => Ok(byte[0]),
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) -> nb::Result<(), T::Error> {
+        self.0.write_all(&[byte])?;
+        Ok(())
+    }
+}
+
+/// An embedded-io port whose line settings can be changed at runtime.
+///
+/// Implemented on the port itself, it makes [`IoSerial`] [`Reconfigure`],
+/// so [`Hc08::change_baud_rate`](crate::Hc08::change_baud_rate) and
+/// [`Hc08::probe`](crate::Hc08::probe) work on embedded-io ports too.
+#[cfg(feature = "eh1")]
+pub trait SetBaud: embedded_io::ErrorType {
+    fn set_baud(&mut self, baud_rate: BaudRate, parity: Parity) -> Result<(), Self::Error>;
+}
+
+#[cfg(feature = "eh1")]
+impl<T> Reconfigure for IoSerial<T>
+where
+    T: embedded_io::Read + embedded_io::Write + embedded_io::ReadReady + SetBaud,
+{
+    fn reconfigure(&mut self, baud_rate: BaudRate, parity: Parity) -> Result<(), T::Error> {
+        self.0.set_baud(baud_rate, parity)
+    }
+}
+
+/// Adapts an embedded-hal 1.0 `DelayNs` to [`Delay`].
+#[cfg(feature = "eh1")]
+#[derive(Debug)]
+pub struct NsDelay<D>(D);
+
+#[cfg(feature = "eh1")]
+impl<D> NsDelay<D> {
+    pub fn new(inner: D) -> Self {
+        Self(inner)
+    }
+
+    pub fn into_inner(self) -> D {
+        self.0
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<D> Delay for NsDelay<D>
+where
+    D: embedded_hal_1::delay::DelayNs,
+{
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms)
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us)
+    }
+
+    fn poll_interval_us(&self) -> u32 {
+        POLL_INTERVAL_US
+    }
+}
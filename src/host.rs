@@ -0,0 +1,122 @@
+//! Desktop serial ports through the `serialport` crate.
+//!
+//! [`HostSerial`] turns any port `serialport` can open — `/dev/ttyUSB0`, a
+//! pty, `COM4` — into the `Read<u8> + Write<u8>` pair the driver expects, and
+//! [`StdDelay`] sleeps the current thread.
+
+extern crate std;
+
+use std::boxed::Box;
+use std::io::{self, ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+
+use serialport::SerialPort;
+
+use crate::hal::Reconfigure;
+use crate::parameters::baudrate::{BaudRate, Parity};
+
+#[derive(Debug)]
+pub struct HostSerial {
+    port: Box<dyn SerialPort>,
+}
+
+impl HostSerial {
+    /// Opens `path` at `baud_rate`, 8 data bits, no parity, one stop bit.
+    pub fn open(path: &str, baud_rate: BaudRate) -> serialport::Result<Self> {
+        let port = serialport::new(path, baud_rate as u32)
+            .timeout(Duration::from_millis(10))
+            .open()?;
+
+        Ok(Self::from_port(port))
+    }
+
+    pub fn from_port(port: Box<dyn SerialPort>) -> Self {
+        Self { port }
+    }
+
+    pub fn port(&self) -> &dyn SerialPort {
+        self.port.as_ref()
+    }
+
+    pub fn port_mut(&mut self) -> &mut dyn SerialPort {
+        self.port.as_mut()
+    }
+
+    pub fn into_port(self) -> Box<dyn SerialPort> {
+        self.port
+    }
+}
+
+fn would_block(err: io::Error) -> nb::Error<io::Error> {
+    match err.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted => {
+            nb::Error::WouldBlock
+        }
+        _ => nb::Error::Other(err),
+    }
+}
+
+impl embedded_hal::serial::Read<u8> for HostSerial {
+    type Error = io::Error;
+
+    fn read(&mut self) -> nb::Result<u8, io::Error> {
+        if self.port.bytes_to_read().map_err(io::Error::from)? == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let mut byte = [0u8; 1];
+        match self.port.read(&mut byte).map_err(would_block)? {
+            0 => Err(nb::Error::WouldBlock),
+            _ => Ok(byte[0]),
+        }
+    }
+}
+
+impl embedded_hal::serial::Write<u8> for HostSerial {
+    type Error = io::Error;
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), io::Error> {
+        match self.port.write(&[byte]).map_err(would_block)? {
+            0 => Err(nb::Error::WouldBlock),
+            _ => Ok(()),
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), io::Error> {
+        self.port.flush().map_err(would_block)
+    }
+}
+
+impl Reconfigure for HostSerial {
+    fn reconfigure(&mut self, baud_rate: BaudRate, parity: Parity) -> Result<(), io::Error> {
+        let parity = match parity {
+            Parity::None => serialport::Parity::None,
+            Parity::Even => serialport::Parity::Even,
+            Parity::Odd => serialport::Parity::Odd,
+        };
+        self.port.set_baud_rate(baud_rate as u32)?;
+        self.port.set_parity(parity)?;
+        Ok(())
+    }
+}
+
+/// Blocks the current thread.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdDelay;
+
+impl embedded_hal::blocking::delay::DelayMs<u32> for StdDelay {
+    fn delay_ms(&mut self, ms: u32) {
+        std::thread::sleep(Duration::from_millis(ms.into()));
+    }
+}
+
+impl embedded_hal::blocking::delay::DelayUs<u32> for StdDelay {
+    /// Spins, since a thread sleep overshoots delays this short several
+    /// times over.
+    fn delay_us(&mut self, us: u32) {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_micros(us.into()) {
+            std::hint::spin_loop();
+        }
+    }
+}
@@ -1,57 +1,127 @@
 #![no_std]
+pub mod any;
+#[cfg(feature = "async")]
+pub mod asynch;
 pub mod command;
+pub mod config;
+pub mod hal;
+#[cfg(feature = "serialport")]
+pub mod host;
 pub mod parameters;
+pub mod response;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod sleep;
 
+use any::{AnyHc08, Mode};
 use command::{
-    build_change_connect_internal_command, build_change_connect_internal_response,
-    build_change_connect_timeout_command, build_change_connect_timeout_response,
-    build_change_connectable_command, build_change_role_command,
-    build_set_characteristic_uuid_command, build_set_characteristic_uuid_response,
-    build_set_connect_uuid_command, build_set_connect_uuid_response,
-    build_set_service_uuid_command, build_set_service_uuid_response, CHANGE_BROADCAST_BASE,
-    CHANGE_NAME_BASE, CHARACTERISTIC_UUID_RESPONSE, CLEAR_ADDR, CONNECT_UUID_RESPONSE, OK_QUERY,
-    OK_RESPONSE, QUERY_CHARACTERISTIC_UUID, QUERY_CONNECTABLE, QUERY_CONNECT_UUID, QUERY_NAME,
-    QUERY_PARAMS_COMMAND, QUERY_ROLE, QUERY_SERVICE_UUID, QUERY_VERSION, RESET_SETTINGS_COMMAND,
-    SERVICE_UUID_RESPONSE,
+    AtCommand, ChangeAddr, ChangeAdvertisingInterval, ChangeAutoSleep, ChangeBroadcastData,
+    ChangeConnectInternal, ChangeConnectTimeout, ChangeConnectable, ChangeLedMode, ChangeName,
+    ChangePowerMode, ChangeRfPower, ChangeRole, ChangeSerialConfig, ChangeUuid, ClearAddr,
+    QueryAddr, QueryAdvertisingInterval, QueryConnectable, QueryLedMode, QueryName,
+    QueryParameters, QueryPowerMode, QueryRfPower, QueryRole, QuerySerialConfig, QueryUuid,
+    QueryVersion, Reboot, ResetSettings, Setting, Test, UuidSlot, Verify,
 };
+use parameters::addr::Addr;
+use parameters::baudrate::{BaudRate, Parity, SerialConfig};
+use parameters::connectable::IsConnectable;
+use parameters::led_mode::LedMode;
+use parameters::power_mode::PowerMode;
+use parameters::rf_power::RfPower;
 use parameters::uuid::UUID;
+use parameters::ParseError;
+use parameters::{role::Role, Parameters};
+use response::{ResponseReader, LINE_MAX};
+use sleep::Sleeping;
 
 use core::marker::PhantomData;
-use core::panic;
 use core::str::{from_utf8, Utf8Error};
-use parameters::connectable::IsConnectable;
 
-use parameters::ParseError;
-use parameters::{addr::Addr, baudrate::BaudRate, role::Role, Parameters};
+use hal::{Delay, Reconfigure, Serial};
 
-use embedded_hal::blocking::delay::DelayMs;
-use embedded_hal::serial::{Read, Write};
+/// What the driver fails with. `E` is the serial port's receive error and
+/// `W` its transmit error, which many ports share.
 #[derive(Debug)]
-pub enum Error {
-    Read,
-    Write,
+pub enum Error<E, W = E> {
+    /// The serial port reported an error while receiving, e.g. an overrun
+    /// or framing error.
+    Read(E),
+    /// The serial port reported an error while transmitting.
+    Write(W),
     InvalidBaudRate,
     InvalidChannel,
+    /// An argument lies outside the range the module accepts. Nothing is
+    /// sent.
+    OutOfRange,
+    /// The command is not available in the handle's current mode.
+    WrongMode,
+    /// The module answered `ERROR`: it does not know the command or does
+    /// not accept the value.
+    Rejected,
+    /// In verify mode, the module acknowledged a setter but reports
+    /// something else when asked right after.
+    VerifyMismatch {
+        expected: Setting,
+        actual: Setting,
+    },
+    /// The host serial port could not switch to the module's new settings.
+    Reconfigure(E),
     WrongResponse,
+    Timeout,
     ParseError(ParseError),
     Utf8Error(Utf8Error),
 }
 
-impl From<ParseError> for Error {
+impl<E, W> From<ParseError> for Error<E, W> {
     fn from(err: ParseError) -> Self {
-        Self::ParseError(err)
+        match err {
+            ParseError::OutOfRange => Self::OutOfRange,
+            err => Self::ParseError(err),
+        }
     }
 }
 
-impl From<Utf8Error> for Error {
+impl<E, W> From<Utf8Error> for Error<E, W> {
     fn from(err: Utf8Error) -> Self {
         Self::Utf8Error(err)
     }
 }
 
+/// The [`Error`] a handle on serial port `S` fails with.
+pub type Hc08Error<S> = Error<<S as Serial>::ReadError, <S as Serial>::WriteError>;
+
+/// How long a read waits for the module to answer, in milliseconds.
+pub const DEFAULT_TIMEOUT_MS: u32 = 1000;
+
+/// How long to wait between polls of the serial port, in microseconds, with
+/// a [`Delay`] that can wait that briefly. A byte takes 87 µs at 115200
+/// baud, and a UART with a single-byte receive register overruns if it is
+/// not read within that.
+pub const POLL_INTERVAL_US: u32 = 20;
+
+/// How long to wait for another line of a multi-line response before
+/// treating it as complete, in milliseconds.
+pub const LINE_GAP_MS: u32 = 50;
+
+/// How long [`Hc08::probe`] waits for an answer at each baud rate, in
+/// milliseconds.
+pub const PROBE_TIMEOUT_MS: u32 = 100;
+
+/// How long [`Hc08::reboot`] waits between checks that the module is back,
+/// in milliseconds.
+pub const REBOOT_DELAY_MS: u32 = 100;
+
+/// How many times [`Hc08::reboot`] checks before giving up.
+pub const REBOOT_ATTEMPTS: u32 = 20;
+
+/// Longest command [`Hc08::execute`] sends.
+pub(crate) const COMMAND_LEN: usize = 64;
+
 pub struct Hc08<S, D, R, C> {
     serial: S,
     delay: D,
+    timeout_ms: u32,
+    verify: bool,
     role: PhantomData<R>,
     connectable: PhantomData<C>,
 }
@@ -63,19 +133,93 @@ pub struct NonConnectable;
 
 impl<S, D> Hc08<S, D, Master, Connectable>
 where
-    S: Write<u8> + Read<u8>,
-    D: DelayMs<u32>,
+    S: Serial,
+    D: Delay,
 {
-    pub fn new(serial: S, delay: D) -> Self {
-        let mut result = Self {
+    /// Restores the module's factory settings, which leave it as a
+    /// connectable master. On failure the serial port and delay are handed
+    /// back.
+    pub fn try_new(serial: S, delay: D) -> Result<Self, (Hc08Error<S>, S, D)> {
+        let mut result = Self::from_parts(serial, delay);
+        match result.reset_setting() {
+            Ok(()) => Ok(result),
+            Err(e) => Err(result.fail(e)),
+        }
+    }
+
+    /// Wraps a module that is already configured, without resetting it. The
+    /// returned handle matches the role and connectability the module
+    /// reports. On failure the serial port and delay are handed back.
+    pub fn attach(serial: S, delay: D) -> Attached<S, D> {
+        Self::from_parts(serial, delay).into_any()
+    }
+
+    fn from_parts(serial: S, delay: D) -> Self {
+        Self {
             serial,
             delay,
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+            verify: false,
             role: PhantomData::<Master>,
             connectable: PhantomData::<Connectable>,
-        };
+        }
+    }
 
-        result.reset_setting().unwrap();
-        result
+    fn into_any(mut self) -> Attached<S, D> {
+        match self.query_mode() {
+            Ok(mode) => Ok(self.into_any_as(mode)),
+            Err(e) => Err(self.fail(e)),
+        }
+    }
+
+    /// Gives up the handle, keeping the serial port and delay.
+    fn fail(self, error: Hc08Error<S>) -> (Hc08Error<S>, S, D) {
+        (error, self.serial, self.delay)
+    }
+}
+
+/// The attached module, or the error along with the serial port and delay
+/// to try again with.
+type Attached<S, D> = Result<AnyHc08<S, D>, (Hc08Error<S>, S, D)>;
+
+/// The baud rate found and the attached module, or the error along with
+/// the serial port and delay to try again with.
+type Probed<S, D> = Result<(BaudRate, AnyHc08<S, D>), (Hc08Error<S>, S, D)>;
+
+impl<S, D> Hc08<S, D, Master, Connectable>
+where
+    S: Reconfigure,
+    D: Delay,
+{
+    /// Finds the baud rate a module of unknown configuration is set to, then
+    /// [attaches](Self::attach) to it.
+    ///
+    /// Every [`BaudRate`] is tried in turn, without parity, until the module
+    /// answers `AT`. Fails with [`Error::Timeout`] if it never does. On
+    /// failure the serial port and delay are handed back.
+    pub fn probe(serial: S, delay: D) -> Probed<S, D> {
+        let mut result = Self::from_parts(serial, delay);
+        result.set_timeout(PROBE_TIMEOUT_MS);
+
+        for baud_rate in BaudRate::ALL {
+            if let Err(e) = result.serial.reconfigure(baud_rate, Parity::None) {
+                return Err(result.fail(Error::Reconfigure(e)));
+            }
+            result.discard_input();
+
+            match result.execute(&Test) {
+                Ok(()) => {
+                    result.set_timeout(DEFAULT_TIMEOUT_MS);
+                    return result.into_any().map(|hc08| (baud_rate, hc08));
+                }
+                // At the wrong rate the module's answer arrives as garbage,
+                // framing errors or not at all.
+                Err(Error::Read(_) | Error::ParseError(_) | Error::Timeout) => {}
+                Err(e) => return Err(result.fail(e)),
+            }
+        }
+
+        Err(result.fail(Error::Timeout))
     }
 }
 
@@ -83,403 +227,483 @@ type ToCentral<S, D, R, C> = Result<Hc08<S, D, Master, Connectable>, Hc08<S, D,
 type ToPeripheral<S, D, R, C> = Result<Hc08<S, D, Slave, Connectable>, Hc08<S, D, R, C>>;
 type ToObserver<S, D, R, C> = Result<Hc08<S, D, Master, NonConnectable>, Hc08<S, D, R, C>>;
 type ToBroadcast<S, D, R, C> = Result<Hc08<S, D, Slave, NonConnectable>, Hc08<S, D, R, C>>;
+type ToSleeping<S, D, R, C> = Result<Sleeping<S, D, R, C>, Hc08<S, D, R, C>>;
 
 impl<S, D, R, C> Hc08<S, D, R, C>
 where
-    S: Write<u8> + Read<u8>,
-    D: DelayMs<u32>,
+    S: Serial,
+    D: Delay,
 {
-    pub fn write_buffer(&mut self, buffer: &[u8]) -> Result<(), Error> {
+    /// Sets how long a read waits for each byte of a response before
+    /// giving up with [`Error::Timeout`].
+    ///
+    /// The time is counted in waits between polls of the serial port, not
+    /// read from a clock, so time spent inside the port's own calls comes on
+    /// top. With a slow port the real timeout is longer.
+    pub fn set_timeout(&mut self, timeout_ms: u32) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    pub fn timeout(&self) -> u32 {
+        self.timeout_ms
+    }
+
+    /// Turns verify mode on or off. In verify mode every setter that has a
+    /// matching query reads the value back right after, and fails with
+    /// [`Error::VerifyMismatch`] if the module did not take it: some
+    /// firmware acknowledges values it then ignores. Off by default, since
+    /// it doubles the traffic.
+    pub fn set_verify(&mut self, verify: bool) {
+        self.verify = verify;
+    }
+
+    pub fn verify(&self) -> bool {
+        self.verify
+    }
+
+    pub fn write_buffer(&mut self, buffer: &[u8]) -> Result<(), Hc08Error<S>> {
         for ch in buffer {
-            let _ = self.serial.write(*ch);
+            nb::block!(self.serial.write_byte(*ch)).map_err(Error::Write)?;
         }
 
         Ok(())
     }
 
-    pub fn read_buffer(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
-        let mut n = 0;
-        while n < buffer.len() {
-            if let Ok(ch) = self.serial.read() {
-                buffer[n] = ch;
-                n += 1;
+    fn read_byte(&mut self) -> Result<u8, Hc08Error<S>> {
+        self.read_byte_within(self.timeout_ms)
+    }
+
+    fn read_byte_within(&mut self, timeout_ms: u32) -> Result<u8, Hc08Error<S>> {
+        let timeout_us = u64::from(timeout_ms) * 1000;
+        let mut waited_us = 0;
+        loop {
+            match self.serial.read_byte() {
+                Ok(ch) => return Ok(ch),
+                Err(nb::Error::Other(e)) => return Err(Error::Read(e)),
+                Err(nb::Error::WouldBlock) => {}
+            }
+
+            if waited_us >= timeout_us {
+                return Err(Error::Timeout);
             }
+            let interval_us = self.delay.poll_interval_us();
+            self.delay.delay_us(interval_us);
+            waited_us += u64::from(interval_us);
+        }
+    }
+
+    pub fn read_buffer(&mut self, buffer: &mut [u8]) -> Result<(), Hc08Error<S>> {
+        for v in buffer.iter_mut() {
+            *v = self.read_byte()?;
         }
         Ok(())
     }
 
-    fn send_command(&mut self, command: &[u8]) -> Result<(), Error> {
-        self.write_buffer(command)?;
+    /// Sends `command` and decodes the module's answer as it arrives,
+    /// taking a pause of [`LINE_GAP_MS`] as its end. An answer running past
+    /// [`RESPONSE_MAX`](response::RESPONSE_MAX) bytes, as when a connected
+    /// peer streams data through the module, fails with
+    /// [`ParseError::TooLong`].
+    ///
+    /// Every other method goes through here. Called directly it bypasses the
+    /// typestate: a command changing role or connectability leaves the
+    /// handle's type out of date.
+    pub fn execute<A: AtCommand>(&mut self, command: &A) -> Result<A::Response, Hc08Error<S>> {
+        let mut buffer = [0u8; COMMAND_LEN];
+        self.write_buffer(command.encode(&mut buffer)?)?;
+
+        let mut reader = ResponseReader::new(command);
+        let mut timeout_ms = self.timeout_ms;
+        loop {
+            match self.read_byte_within(timeout_ms) {
+                Ok(byte) => reader.push(byte)?,
+                Err(Error::Timeout) if reader.received() => return reader.finish(),
+                Err(e) => return Err(e),
+            }
+            timeout_ms = LINE_GAP_MS;
+        }
+    }
+
+    /// Runs a setter, then reads the value back if in verify mode.
+    fn change<A: Verify>(&mut self, command: &A) -> Result<(), Hc08Error<S>> {
+        let expected = self.expected(command)?;
+        self.execute(command)?;
+        self.read_back(command, expected)
+    }
+
+    /// In verify mode, what `command` should leave the module reporting.
+    /// Worked out before the command is sent, so a value that could never
+    /// verify is refused without changing the module.
+    fn expected<A: Verify>(&self, command: &A) -> Result<Option<Setting>, Hc08Error<S>> {
+        if !self.verify {
+            return Ok(None);
+        }
+
+        Ok(Some(command.expected()?))
+    }
 
-        if self.wait_ok_response() {
+    /// Checks that the module reports `expected`, from
+    /// [`expected`](Self::expected).
+    fn read_back<A: Verify>(
+        &mut self,
+        command: &A,
+        expected: Option<Setting>,
+    ) -> Result<(), Hc08Error<S>> {
+        let Some(expected) = expected else {
+            return Ok(());
+        };
+
+        let actual = command.actual(self.execute(&command.query())?);
+        if actual == expected {
             Ok(())
         } else {
-            Err(Error::WrongResponse)
+            Err(Error::VerifyMismatch { expected, actual })
         }
     }
 
-    fn wait_ok_response(&mut self) -> bool {
-        let mut buffer = [0u8; 2];
-        self.read_buffer(&mut buffer).unwrap();
-        buffer == OK_RESPONSE
+    pub fn change_name(&mut self, name: &str) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeName(name))
     }
 
-    pub fn change_name(&mut self, name: &str) -> Result<(), Error> {
-        self.write_buffer(&CHANGE_NAME_BASE)?;
-        self.write_buffer(name.as_bytes())?;
+    pub fn query_connectable(&mut self) -> Result<IsConnectable, Hc08Error<S>> {
+        self.execute(&QueryConnectable)
+    }
 
-        if !self.wait_ok_response() {
-            Err(Error::WrongResponse)
-        } else {
-            Ok(())
-        }
+    pub fn query_role(&mut self) -> Result<Role, Hc08Error<S>> {
+        self.execute(&QueryRole)
+    }
+
+    pub fn reset_setting(&mut self) -> Result<(), Hc08Error<S>> {
+        self.execute(&ResetSettings)
+    }
+
+    pub fn is_ok(&mut self) -> bool {
+        self.execute(&Test).is_ok()
     }
 
-    pub fn query_connectable(&mut self) -> Result<IsConnectable, Error> {
-        self.write_buffer(&QUERY_CONNECTABLE)?;
-        let mut buffer = [0u8; 15];
-        let mut n = 0;
-        // while n < buffer.len() {
-        //     if n == 11 && buffer[0] == b'C' {
-        //         break;
-        //     }
-
-        //     if let Ok(ch) = self.serial.read() {
-        //         buffer[n] = ch;
-        //         n += 1;
-        //     }
-        // }
-
-        // let c = IsConnectable::try_from(&buffer[0..n])?;
-        if let Ok(ch) = self.serial.read() {
-            if ch == b'C' {
-                Ok(IsConnectable(true))
-            } else if ch == b'N' {
-                Ok(IsConnectable(false))
-            } else {
-                Err(Error::WrongResponse)
+    /// Restarts the module, keeping its settings, and waits until it
+    /// answers `AT` again. Whatever it prints while booting is dropped.
+    pub fn reboot(&mut self) -> Result<(), Hc08Error<S>> {
+        self.execute(&Reboot)?;
+
+        let timeout_ms = self.timeout_ms;
+        self.timeout_ms = PROBE_TIMEOUT_MS;
+        let mut result = Err(Error::Timeout);
+        for _ in 0..REBOOT_ATTEMPTS {
+            self.delay.delay_ms(REBOOT_DELAY_MS);
+            self.discard_until_idle();
+            match self.execute(&Test) {
+                Ok(()) => {
+                    result = Ok(());
+                    break;
+                }
+                Err(Error::Read(_) | Error::ParseError(_) | Error::Timeout) => {}
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
             }
-        } else {
-            Err(Error::Read)
         }
-        // Ok(c)
+
+        self.timeout_ms = timeout_ms;
+        result
     }
 
-    pub fn query_role(&mut self) -> Result<Role, Error> {
-        self.write_buffer(&QUERY_ROLE)?;
-        let mut buffer = [0u8; 6];
-        let mut n = 0;
-        while n < buffer.len() {
-            if n == 5 && buffer[0] == b'S' {
+    /// Drops input until the module has been quiet for [`LINE_GAP_MS`].
+    fn discard_until_idle(&mut self) {
+        // Bounded, in case the module never stops talking.
+        for _ in 0..LINE_MAX * 8 {
+            if let Err(Error::Timeout) = self.read_byte_within(LINE_GAP_MS) {
                 break;
             }
+        }
+    }
 
-            if let Ok(ch) = self.serial.read() {
-                buffer[n] = ch;
-                n += 1;
+    /// Drops whatever the module has sent that nobody read.
+    fn discard_input(&mut self) {
+        // Bounded, in case the port keeps reporting errors.
+        for _ in 0..LINE_MAX {
+            if let Err(nb::Error::WouldBlock) = self.serial.read_byte() {
+                break;
             }
         }
+    }
 
-        let s = from_utf8(&buffer[0..n])?;
-        let role = Role::try_from(s.trim_end())?;
+    pub fn get_parameters(&mut self) -> Result<Parameters, Hc08Error<S>> {
+        self.execute(&QueryParameters)
+    }
 
-        Ok(role)
+    /// The firmware version, copied into `buffer`.
+    pub fn get_version<'a>(&mut self, buffer: &'a mut [u8; 21]) -> Result<&'a str, Hc08Error<S>> {
+        let version = self.execute(&QueryVersion)?;
+        copy_str(version.as_str(), buffer)
     }
 
-    pub fn reset_setting(&mut self) -> Result<(), Error> {
-        self.write_buffer(&RESET_SETTINGS_COMMAND)?;
+    /// The device name, copied into `buffer`.
+    pub fn get_name<'a>(&mut self, buffer: &'a mut [u8]) -> Result<&'a str, Hc08Error<S>> {
+        let name = self.execute(&QueryName)?;
+        copy_str(name.as_str(), buffer)
+    }
 
-        if !self.wait_ok_response() {
-            Err(Error::WrongResponse)
-        } else {
-            Ok(())
-        }
+    pub fn query_serial_config(&mut self) -> Result<SerialConfig, Hc08Error<S>> {
+        self.execute(&QuerySerialConfig)
     }
 
-    pub fn is_ok(&mut self) -> bool {
-        self.write_buffer(&OK_QUERY).unwrap();
-        self.wait_ok_response()
-    }
-
-    pub fn get_parameters(&mut self) -> Result<Parameters, Error> {
-        self.write_buffer(&QUERY_PARAMS_COMMAND)?;
-
-        let mut params = [[0u8; 24]; 8];
-        let mut param_slices: [&[u8]; 8] = Default::default();
-        for (pi, p) in &mut params.iter_mut().enumerate() {
-            for (i, v) in p.iter_mut().enumerate() {
-                if let Ok(ch) = self.serial.read() {
-                    *v = ch;
-                    if ch == b'\n' {
-                        param_slices[pi] = &p[..=i];
-                        break;
-                    }
-                }
-            }
-        }
+    pub fn query_addr(&mut self) -> Result<Addr, Hc08Error<S>> {
+        self.execute(&QueryAddr)
+    }
 
-        let role = Role::try_from(param_slices[1])?;
-        let baud_rate = BaudRate::try_from(param_slices[2])?;
-        let addr = Addr::try_from(param_slices[3])?;
+    /// Sets the address the module advertises with.
+    pub fn change_addr(&mut self, addr: Addr) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeAddr(addr))
+    }
 
-        Ok(Parameters {
-            role,
-            baud_rate,
-            addr,
-        })
+    pub fn query_rf_power(&mut self) -> Result<RfPower, Hc08Error<S>> {
+        self.execute(&QueryRfPower)
     }
 
-    pub fn get_version<'a>(&mut self, buffer: &'a mut [u8; 21]) -> Result<&'a str, Error> {
-        self.write_buffer(&QUERY_VERSION)?;
-        self.read_buffer(buffer)?;
+    pub fn change_rf_power(&mut self, power: RfPower) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeRfPower(power))
+    }
 
-        match from_utf8(buffer) {
-            Ok(s) => Ok(s),
-            Err(_e) => Err(Error::WrongResponse),
-        }
+    pub fn query_power_mode(&mut self) -> Result<PowerMode, Hc08Error<S>> {
+        self.execute(&QueryPowerMode)
+    }
+
+    pub fn change_power_mode(&mut self, mode: PowerMode) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangePowerMode(mode))
     }
 
-    pub fn get_name<'a>(&mut self, buffer: &'a mut [u8]) -> Result<&'a str, Error> {
-        self.write_buffer(&QUERY_NAME)?;
-        self.read_buffer(buffer)?;
+    /// Sets how long the serial line must stay idle before the module goes
+    /// to sleep in [`PowerMode::LowPower2`].
+    pub fn change_auto_sleep(&mut self, seconds: u32) -> Result<(), Hc08Error<S>> {
+        self.execute(&ChangeAutoSleep(seconds))
+    }
 
-        match from_utf8(buffer) {
-            Ok(s) => Ok(s),
-            Err(_e) => Err(Error::WrongResponse),
+    /// Hands the handle over while the module sleeps. Only the power mode
+    /// is queried; the module falls asleep on its own once the auto-sleep
+    /// time has passed. The handle is returned if the module is not in
+    /// [`PowerMode::LowPower2`], and so would never sleep, or does not
+    /// answer.
+    pub fn into_sleeping(mut self) -> ToSleeping<S, D, R, C> {
+        match self.query_power_mode() {
+            Ok(PowerMode::LowPower2) => Ok(Sleeping::new(self)),
+            _ => Err(self),
         }
     }
 
-    pub fn change_role(&mut self, role: Role) -> Result<(), Error> {
-        let cmd = build_change_role_command(role);
-        self.send_command(&cmd)
+    pub fn query_led_mode(&mut self) -> Result<LedMode, Hc08Error<S>> {
+        self.execute(&QueryLedMode)
     }
 
-    pub fn change_connectable(&mut self, c: IsConnectable) -> Result<(), Error> {
-        let cmd = build_change_connectable_command(c);
-
-        self.send_command(&cmd)
+    pub fn change_led_mode(&mut self, mode: LedMode) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeLedMode(mode))
     }
 
-    pub fn into_central_mode(mut self) -> ToCentral<S, D, R, C> {
-        if let Err(_) = self.change_role(Role::Master) {
-            return Err(self);
-        }
-        self.delay.delay_ms(200);
+    pub fn change_role(&mut self, role: Role) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeRole(role))
+    }
 
-        if let Err(_) = self.change_connectable(IsConnectable(true)) {
-            return Err(self);
-        }
-        self.delay.delay_ms(200);
+    pub fn change_connectable(&mut self, c: IsConnectable) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeConnectable(c))
+    }
 
-        Ok(Hc08 {
+    fn into_state<R2, C2>(self) -> Hc08<S, D, R2, C2> {
+        Hc08 {
             serial: self.serial,
             delay: self.delay,
-            role: PhantomData::<Master>,
-            connectable: PhantomData::<Connectable>,
-        })
+            timeout_ms: self.timeout_ms,
+            verify: self.verify,
+            role: PhantomData,
+            connectable: PhantomData,
+        }
     }
 
-    pub fn into_peripheral_mode(mut self) -> ToPeripheral<S, D, R, C> {
-        if let Err(_) = self.change_role(Role::Slave) {
-            return Err(self);
+    /// Wraps the handle as the typestate for `mode`, which the module is
+    /// known to be in.
+    fn into_any_as(self, mode: Mode) -> AnyHc08<S, D> {
+        match mode {
+            Mode::Central => AnyHc08::Central(self.into_state()),
+            Mode::Peripheral => AnyHc08::Peripheral(self.into_state()),
+            Mode::Observer => AnyHc08::Observer(self.into_state()),
+            Mode::Broadcast => AnyHc08::Broadcast(self.into_state()),
         }
+    }
+
+    pub fn query_mode(&mut self) -> Result<Mode, Hc08Error<S>> {
+        let role = self.query_role()?;
+        let connectable = self.query_connectable()?;
+        Ok(Mode::new(role, connectable))
+    }
+
+    /// Changes role and connectability, then reads both back to make sure
+    /// the module really is in `mode`.
+    fn switch_mode(&mut self, mode: Mode) -> Result<(), Hc08Error<S>> {
+        self.change_role(mode.role())?;
         self.delay.delay_ms(200);
 
-        if let Err(_) = self.change_connectable(IsConnectable(true)) {
-            return Err(self);
-        }
+        self.change_connectable(mode.connectable())?;
         self.delay.delay_ms(200);
 
-        Ok(Hc08 {
-            serial: self.serial,
-            delay: self.delay,
-            role: PhantomData::<Slave>,
-            connectable: PhantomData::<Connectable>,
-        })
+        if self.query_mode()? == mode {
+            Ok(())
+        } else {
+            Err(Error::WrongResponse)
+        }
     }
 
-    pub fn into_observer_mode(mut self) -> ToObserver<S, D, R, C> {
-        if let Err(_) = self.change_role(Role::Master) {
-            return Err(self);
+    pub fn into_central_mode(mut self) -> ToCentral<S, D, R, C> {
+        match self.switch_mode(Mode::Central) {
+            Ok(()) => Ok(self.into_state()),
+            Err(_) => Err(self),
         }
-        self.delay.delay_ms(200);
+    }
 
-        if let Err(_) = self.change_connectable(IsConnectable(false)) {
-            return Err(self);
+    pub fn into_peripheral_mode(mut self) -> ToPeripheral<S, D, R, C> {
+        match self.switch_mode(Mode::Peripheral) {
+            Ok(()) => Ok(self.into_state()),
+            Err(_) => Err(self),
         }
-        self.delay.delay_ms(200);
+    }
 
-        Ok(Hc08 {
-            serial: self.serial,
-            delay: self.delay,
-            role: PhantomData::<Master>,
-            connectable: PhantomData::<NonConnectable>,
-        })
+    pub fn into_observer_mode(mut self) -> ToObserver<S, D, R, C> {
+        match self.switch_mode(Mode::Observer) {
+            Ok(()) => Ok(self.into_state()),
+            Err(_) => Err(self),
+        }
     }
 
     pub fn into_broadcast_mode(mut self) -> ToBroadcast<S, D, R, C> {
-        if let Err(_) = self.change_role(Role::Slave) {
-            return Err(self);
+        match self.switch_mode(Mode::Broadcast) {
+            Ok(()) => Ok(self.into_state()),
+            Err(_) => Err(self),
         }
-        self.delay.delay_ms(200);
+    }
+}
+
+impl<S, D, R, C> Hc08<S, D, R, C>
+where
+    S: Reconfigure,
+    D: Delay,
+{
+    /// Changes the module's UART settings and moves the host port over to
+    /// them, then checks that the module still answers.
+    ///
+    /// The module acknowledges at the old settings and switches right after,
+    /// so a failure after the acknowledgement leaves the two sides out of
+    /// step; reconnecting at `baud_rate` is the way back.
+    pub fn change_baud_rate(
+        &mut self,
+        baud_rate: BaudRate,
+        parity: Parity,
+    ) -> Result<(), Hc08Error<S>> {
+        let command = ChangeSerialConfig(SerialConfig {
+            baud: baud_rate,
+            parity,
+        });
+        let expected = self.expected(&command)?;
+        self.execute(&command)?;
 
-        if let Err(_) = self.change_connectable(IsConnectable(false)) {
-            return Err(self);
-        }
         self.delay.delay_ms(200);
+        self.serial
+            .reconfigure(baud_rate, parity)
+            .map_err(Error::Reconfigure)?;
 
-        Ok(Hc08 {
-            serial: self.serial,
-            delay: self.delay,
-            role: PhantomData::<Slave>,
-            connectable: PhantomData::<NonConnectable>,
-        })
+        self.execute(&Test)?;
+        self.read_back(&command, expected)
+    }
+
+    /// [`change_baud_rate`](Self::change_baud_rate) taking both settings at
+    /// once.
+    pub fn change_serial_config(&mut self, config: SerialConfig) -> Result<(), Hc08Error<S>> {
+        self.change_baud_rate(config.baud, config.parity)
     }
 }
 
-impl<S, D> Hc08<S, D, Slave, NonConnectable>
+impl<S, D, C> Hc08<S, D, Slave, C>
 where
-    S: Write<u8> + Read<u8>,
-    D: DelayMs<u32>,
+    S: Serial,
+    D: Delay,
 {
-    pub fn change_broadcast_data(&mut self, data: &[u8]) -> Result<(), Error> {
-        self.write_buffer(&CHANGE_BROADCAST_BASE)?;
-        self.write_buffer(data)?;
+    /// Sets how often the module advertises, in milliseconds. The module
+    /// counts in 0.625 ms steps, so `ms` is rounded down to one.
+    pub fn change_advertising_interval(&mut self, ms: u32) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeAdvertisingInterval(ms))
+    }
 
-        if !self.wait_ok_response() {
-            Err(Error::WrongResponse)
-        } else {
-            Ok(())
-        }
+    /// The advertising interval in milliseconds, rounded down.
+    pub fn query_advertising_interval(&mut self) -> Result<u32, Hc08Error<S>> {
+        self.execute(&QueryAdvertisingInterval)
     }
 }
 
-impl<S, D, R> Hc08<S, D, R, Connectable>
+impl<S, D> Hc08<S, D, Slave, NonConnectable>
 where
-    S: Write<u8> + Read<u8>,
-    D: DelayMs<u32>,
+    S: Serial,
+    D: Delay,
 {
-    pub fn change_connect_internal(&mut self, min: u32, max: u32) -> Result<(), Error> {
-        let mut buffer = [0; 20];
-        let cmd = build_change_connect_internal_command(min, max, &mut buffer);
-        self.write_buffer(cmd)?;
-
-        let expect = build_change_connect_internal_response(min, max, &mut buffer);
-        let mut response = [0u8; 20];
-        self.read_buffer(&mut response[..expect.len()]).unwrap();
-        if expect == response {
-            Ok(())
-        } else {
-            Err(Error::WrongResponse)
-        }
+    pub fn change_broadcast_data(&mut self, data: &[u8]) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeBroadcastData(data))
     }
+}
 
-    pub fn change_connect_timeout(&mut self, time: u32) -> Result<(), Error> {
-        let mut buffer = [0; 20];
-        let cmd = build_change_connect_timeout_command(time, &mut buffer);
-        self.write_buffer(cmd)?;
+impl<S, D, R> Hc08<S, D, R, Connectable>
+where
+    S: Serial,
+    D: Delay,
+{
+    pub fn change_connect_internal(&mut self, min: u32, max: u32) -> Result<(), Hc08Error<S>> {
+        self.execute(&ChangeConnectInternal { min, max })
+    }
 
-        let expect = build_change_connect_timeout_response(time, &mut buffer);
-        let mut response = [0u8; 20];
-        self.read_buffer(&mut response[..expect.len()]).unwrap();
-        if expect == response {
-            Ok(())
-        } else {
-            Err(Error::WrongResponse)
-        }
+    pub fn change_connect_timeout(&mut self, time: u32) -> Result<(), Hc08Error<S>> {
+        self.execute(&ChangeConnectTimeout(time))
     }
 }
 
 impl<S, D> Hc08<S, D, Master, Connectable>
 where
-    S: Write<u8> + Read<u8>,
-    D: DelayMs<u32>,
+    S: Serial,
+    D: Delay,
 {
-    pub fn clear_slave_addr(&mut self) -> Result<(), Error> {
-        self.send_command(&CLEAR_ADDR)
+    pub fn clear_slave_addr(&mut self) -> Result<(), Hc08Error<S>> {
+        self.execute(&ClearAddr)
     }
 
-    pub fn query_connect_uuid(&mut self) -> Result<UUID, Error> {
-        self.write_buffer(&QUERY_CONNECT_UUID)?;
-        let mut buffer = [0; 13];
-        self.read_buffer(&mut buffer)?;
-
-        if buffer[..10] != CONNECT_UUID_RESPONSE {
-            Err(Error::WrongResponse)
-        } else {
-            Ok(UUID::try_from(&buffer[10..])?)
-        }
+    pub fn query_connect_uuid(&mut self) -> Result<UUID, Hc08Error<S>> {
+        self.execute(&QueryUuid(UuidSlot::Connect))
     }
 
-    pub fn set_connect_uuid(&mut self, uuid: UUID) -> Result<(), Error> {
-        let cmd = build_set_connect_uuid_command(uuid);
-        self.write_buffer(&cmd)?;
-
-        let expect = build_set_connect_uuid_response(uuid);
-        let mut response = [0u8; 13];
-        self.read_buffer(&mut response).unwrap();
-        if expect == response {
-            Ok(())
-        } else {
-            Err(Error::WrongResponse)
-        }
+    pub fn set_connect_uuid(&mut self, uuid: UUID) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeUuid(UuidSlot::Connect, uuid))
     }
 }
 
 impl<S, D> Hc08<S, D, Slave, Connectable>
 where
-    S: Write<u8> + Read<u8>,
-    D: DelayMs<u32>,
+    S: Serial,
+    D: Delay,
 {
-    pub fn get_service_uuid(&mut self) -> Result<UUID, Error> {
-        self.write_buffer(&QUERY_SERVICE_UUID)?;
-        let mut buffer = [0; 13];
-        self.read_buffer(&mut buffer)?;
-
-        if buffer[..10] != SERVICE_UUID_RESPONSE {
-            Err(Error::WrongResponse)
-        } else {
-            Ok(UUID::try_from(&buffer[10..])?)
-        }
+    pub fn get_service_uuid(&mut self) -> Result<UUID, Hc08Error<S>> {
+        self.execute(&QueryUuid(UuidSlot::Service))
     }
 
-    pub fn set_service_uuid(&mut self, uuid: UUID) -> Result<(), Error> {
-        let cmd = build_set_service_uuid_command(uuid);
-        self.write_buffer(&cmd)?;
-
-        let expect = build_set_service_uuid_response(uuid);
-        let mut response = [0u8; 13];
-        self.read_buffer(&mut response).unwrap();
-        if expect == response {
-            Ok(())
-        } else {
-            Err(Error::WrongResponse)
-        }
+    pub fn set_service_uuid(&mut self, uuid: UUID) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeUuid(UuidSlot::Service, uuid))
     }
 
-    pub fn get_characteristic_uuid(&mut self) -> Result<UUID, Error> {
-        self.write_buffer(&QUERY_CHARACTERISTIC_UUID)?;
-        let mut buffer = [0; 13];
-        self.read_buffer(&mut buffer)?;
-
-        if buffer[..10] != CHARACTERISTIC_UUID_RESPONSE {
-            Err(Error::WrongResponse)
-        } else {
-            Ok(UUID::try_from(&buffer[10..])?)
-        }
+    pub fn get_characteristic_uuid(&mut self) -> Result<UUID, Hc08Error<S>> {
+        self.execute(&QueryUuid(UuidSlot::Characteristic))
     }
 
-    pub fn set_characteristic_uuid(&mut self, uuid: UUID) -> Result<(), Error> {
-        let cmd = build_set_characteristic_uuid_command(uuid);
-        self.write_buffer(&cmd)?;
-
-        let expect = build_set_characteristic_uuid_response(uuid);
-        let mut response = [0u8; 13];
-        self.read_buffer(&mut response).unwrap();
-        if expect == response {
-            Ok(())
-        } else {
-            Err(Error::WrongResponse)
-        }
+    pub fn set_characteristic_uuid(&mut self, uuid: UUID) -> Result<(), Hc08Error<S>> {
+        self.change(&ChangeUuid(UuidSlot::Characteristic, uuid))
     }
 }
+
+/// Copies `s` to the start of `buffer`, failing if it does not fit.
+pub(crate) fn copy_str<'a, E, W>(s: &str, buffer: &'a mut [u8]) -> Result<&'a str, Error<E, W>> {
+    let buffer = buffer.get_mut(..s.len()).ok_or(ParseError::TooLong)?;
+    buffer.copy_from_slice(s.as_bytes());
+    Ok(from_utf8(buffer)?)
+}
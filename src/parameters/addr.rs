@@ -1,29 +1,214 @@
-use super::ParseError;
-use core::str::from_utf8;
+use core::fmt;
+use core::str::FromStr;
 
+use super::{line_value, ParseError};
+
+/// A Bluetooth device address, most significant byte first.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Addr([u8; 6]);
 
-impl TryFrom<&[u8]> for Addr {
-    type Error = ParseError;
+/// The sub-types of a random address, told apart by its two most
+/// significant bits.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RandomKind {
+    /// `0b11`: fixed for the lifetime of the device, or until power cycle.
+    Static,
+    /// `0b01`: derived from an identity key and rotated.
+    ResolvablePrivate,
+    /// `0b00`: rotated and not linked to an identity.
+    NonResolvablePrivate,
+    /// `0b10`: not assigned by the specification.
+    Reserved,
+}
 
-    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        if &value[0..5] != b"Addr:" {
-            return Err(ParseError::PrefixError);
+impl Addr {
+    pub const fn new(bytes: [u8; 6]) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn bytes(&self) -> [u8; 6] {
+        self.0
+    }
+
+    /// What kind of random address this would be. Whether the address is
+    /// random at all is not encoded in it.
+    pub const fn random_kind(&self) -> RandomKind {
+        match self.0[0] >> 6 {
+            0b11 => RandomKind::Static,
+            0b01 => RandomKind::ResolvablePrivate,
+            0b00 => RandomKind::NonResolvablePrivate,
+            _ => RandomKind::Reserved,
+        }
+    }
+
+    /// Whether this is valid as a static random address: top bits `0b11`,
+    /// and the rest neither all zeros nor all ones.
+    pub fn is_static_random(&self) -> bool {
+        let mut rest = self.0;
+        rest[0] &= 0x3F;
+        matches!(self.random_kind(), RandomKind::Static)
+            && rest != [0; 6]
+            && rest != [0x3F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]
+    }
+
+    fn try_from_hex(s: &str) -> Result<Self, ParseError> {
+        let mut addr = [0; 6];
+        for (i, v) in addr.iter_mut().enumerate() {
+            let part = s.get(2 * i..2 * i + 2).ok_or(ParseError::WrongValue)?;
+            *v = parse_byte(part)?;
         }
 
-        if &value[value.len() - 2..value.len()] != b"\r\n" {
-            return Err(ParseError::WithoutNewline);
+        Ok(Addr(addr))
+    }
+
+    /// The form `AT+ADDR` takes and reports: twelve hex digits.
+    pub fn to_hex(&self) -> [u8; 12] {
+        const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+        let mut result = [0; 12];
+        for (i, byte) in self.0.iter().enumerate() {
+            result[2 * i] = HEX_DIGITS[(byte >> 4) as usize];
+            result[2 * i + 1] = HEX_DIGITS[(byte & 0xF) as usize];
         }
 
-        let s = from_utf8(&value[5..value.len() - 2])?;
+        result
+    }
+}
+
+impl From<[u8; 6]> for Addr {
+    fn from(bytes: [u8; 6]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Addr> for [u8; 6] {
+    fn from(addr: Addr) -> Self {
+        addr.0
+    }
+}
+
+impl TryFrom<&str> for Addr {
+    type Error = ParseError;
+    // 48,87,2D,12,34,56 or 48:87:2D:12:34:56 or 48872D123456
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        if s.len() == 12 {
+            return Self::try_from_hex(s);
+        }
 
         let mut addr = [0; 6];
-        (0..s.len())
-            .step_by(3)
-            .enumerate()
-            .for_each(|(ai, i)| addr[ai] = u8::from_str_radix(&s[i..i + 2], 16).unwrap());
+        let mut parts = s.split([',', ':']);
+        for v in addr.iter_mut() {
+            let part = parts.next().ok_or(ParseError::WrongValue)?;
+            *v = parse_byte(part)?;
+        }
+
+        if parts.next().is_some() {
+            return Err(ParseError::WrongValue);
+        }
 
         Ok(Addr(addr))
     }
 }
+
+fn parse_byte(part: &str) -> Result<u8, ParseError> {
+    if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ParseError::WrongValue);
+    }
+
+    Ok(u8::from_str_radix(part, 16)?)
+}
+
+impl FromStr for Addr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl TryFrom<&[u8]> for Addr {
+    type Error = ParseError;
+    // Addr:48,87,2D,12,34,56
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        line_value(value, "Addr", |value| Self::try_from(value))
+    }
+}
+
+impl fmt::Display for Addr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a0, a1, a2, a3, a4, a5] = self.0;
+        write!(
+            f,
+            "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+            a0, a1, a2, a3, a4, a5
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::string::ToString;
+
+    #[test]
+    fn parse_addr_line() {
+        let addr = Addr::try_from(&b"Addr:48,87,2D,12,34,56\r\n"[..]).unwrap();
+        assert_eq!(addr, Addr([0x48, 0x87, 0x2D, 0x12, 0x34, 0x56]));
+    }
+
+    #[test]
+    fn malformed_addr_line_is_an_error() {
+        for line in [
+            &b""[..],
+            b"Addr:",
+            b"Addr:\r\n",
+            b"Addr:48,87,2D,12,34\r\n",
+            b"Addr:48,87,2D,12,34,56,78\r\n",
+            b"Addr:48,87,2D,12,34,5\r\n",
+            b"Addr:48,87,2D,12,34,+5\r\n",
+            b"Addr:48,87,2D,12,34,\xc3\xa9\r\n",
+            b"Addr:4887,2D,12,34,56\r\n",
+            b"Addr:48,87,2D,12,34,56",
+            b"Addr:48872D12345G\r\n",
+            b"Addr:48872D1234\xc3\xa9\r\n",
+        ] {
+            assert!(Addr::try_from(line).is_err());
+        }
+    }
+
+    #[test]
+    fn addr_text_forms() {
+        let addr = Addr::new([0x48, 0x87, 0x2D, 0xAB, 0xCD, 0xEF]);
+        assert_eq!(addr.to_string(), "48:87:2D:AB:CD:EF");
+        assert_eq!("48:87:2D:AB:CD:EF".parse::<Addr>().unwrap(), addr);
+        assert_eq!("48:87:2d:ab:cd:ef".parse::<Addr>().unwrap(), addr);
+        assert_eq!(&addr.to_hex(), b"48872DABCDEF");
+        assert_eq!(Addr::try_from("48872DABCDEF").unwrap(), addr);
+        assert_eq!(<[u8; 6]>::from(addr), addr.bytes());
+    }
+
+    #[test]
+    fn random_address_kinds() {
+        let addr = Addr::new([0xC0, 0, 0, 0, 0, 1]);
+        assert_eq!(addr.random_kind(), RandomKind::Static);
+        assert!(addr.is_static_random());
+
+        assert!(!Addr::new([0xC0, 0, 0, 0, 0, 0]).is_static_random());
+        assert!(!Addr::new([0xFF; 6]).is_static_random());
+        assert_eq!(
+            Addr::new([0x48, 0, 0, 0, 0, 1]).random_kind(),
+            RandomKind::ResolvablePrivate
+        );
+        assert_eq!(
+            Addr::new([0x08, 0, 0, 0, 0, 1]).random_kind(),
+            RandomKind::NonResolvablePrivate
+        );
+        assert_eq!(
+            Addr::new([0x88, 0, 0, 0, 0, 1]).random_kind(),
+            RandomKind::Reserved
+        );
+        assert!(!Addr::new([0x48, 0, 0, 0, 0, 1]).is_static_random());
+    }
+}
@@ -1,5 +1,6 @@
-use super::ParseError;
-use core::str::from_utf8;
+use core::fmt;
+
+use super::{line_value, ParseError};
 
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -22,6 +23,105 @@ pub enum BaudRate {
     Bps115200 = 115200,
 }
 
+impl BaudRate {
+    /// Every rate the module supports, slowest first.
+    pub const ALL: [BaudRate; 8] = [
+        BaudRate::Bps1200,
+        BaudRate::Bps2400,
+        BaudRate::Bps4800,
+        BaudRate::Bps9600,
+        BaudRate::Bps19200,
+        BaudRate::Bps38400,
+        BaudRate::Bps57600,
+        BaudRate::Bps115200,
+    ];
+}
+
+/// Parity of the module's UART.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Parity {
+    None,
+    Even,
+    Odd,
+}
+
+impl Parity {
+    /// The letter `AT+BAUD=` takes.
+    pub fn code(self) -> u8 {
+        match self {
+            Parity::None => b'N',
+            Parity::Even => b'E',
+            Parity::Odd => b'O',
+        }
+    }
+
+    /// The word `AT+RX` reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Parity::None => "NONE",
+            Parity::Even => "EVEN",
+            Parity::Odd => "ODD",
+        }
+    }
+}
+
+impl TryFrom<&str> for Parity {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "NONE" => Ok(Parity::None),
+            "EVEN" => Ok(Parity::Even),
+            "ODD" => Ok(Parity::Odd),
+            _ => Err(ParseError::WrongValue),
+        }
+    }
+}
+
+/// The module's UART settings, as `AT+RX` reports them: `9600,NONE`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SerialConfig {
+    pub baud: BaudRate,
+    pub parity: Parity,
+}
+
+impl Default for SerialConfig {
+    /// Factory settings.
+    fn default() -> Self {
+        Self {
+            baud: BaudRate::Bps9600,
+            parity: Parity::None,
+        }
+    }
+}
+
+impl TryFrom<&str> for SerialConfig {
+    type Error = ParseError;
+    // 9600,NONE
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (baud, parity) = value.split_once(',').ok_or(ParseError::WrongValue)?;
+
+        Ok(Self {
+            baud: BaudRate::try_from(baud.parse::<i32>()?)?,
+            parity: Parity::try_from(parity)?,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for SerialConfig {
+    type Error = ParseError;
+    // Baud:9600,NONE
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        line_value(value, "Baud", |value| Self::try_from(value))
+    }
+}
+
+impl fmt::Display for SerialConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.baud as u32, self.parity.name())
+    }
+}
+
 impl TryFrom<i32> for BaudRate {
     type Error = ParseError;
 
@@ -40,22 +140,69 @@ impl TryFrom<i32> for BaudRate {
     }
 }
 
+impl TryFrom<&str> for BaudRate {
+    type Error = ParseError;
+    // 9600,NONE
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(SerialConfig::try_from(value)?.baud)
+    }
+}
+
 impl TryFrom<&[u8]> for BaudRate {
     type Error = ParseError;
     // Baud:9600,NONE
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        if &value[0..5] != b"Baud:" {
-            return Err(ParseError::PrefixError);
-        }
+        line_value(value, "Baud", |value| Self::try_from(value))
+    }
+}
 
-        if &value[value.len() - 2..value.len()] != b"\r\n" {
-            return Err(ParseError::WithoutNewline);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_baud_line() {
+        let baud = BaudRate::try_from(&b"Baud:9600,NONE\r\n"[..]).unwrap();
+        assert_eq!(baud, BaudRate::Bps9600);
+    }
+
+    #[test]
+    fn malformed_baud_line_is_an_error() {
+        for line in [
+            &b""[..],
+            b"Baud",
+            b"Baud:\r\n",
+            b"Baud:9600\r\n",
+            b"Baud:9601,NONE\r\n",
+            b"Baud:,NONE\r\n",
+            b"Baud:99999999999,NONE\r\n",
+            b"Baud:\xff,NONE\r\n",
+            b"Baud:9600,MARK\r\n",
+            b"Baud:9600,none\r\n",
+        ] {
+            assert!(BaudRate::try_from(line).is_err());
+            assert!(SerialConfig::try_from(line).is_err());
         }
+    }
 
-        let s = from_utf8(&value[5..value.len() - 2])?;
-        let (buad, _crc) = s.split_once(',').ok_or(ParseError::WrongValue)?;
+    #[test]
+    fn serial_config_keeps_parity() {
+        let config = SerialConfig::try_from(&b"Baud:19200,EVEN\r\n"[..]).unwrap();
+        assert_eq!(config.baud, BaudRate::Bps19200);
+        assert_eq!(config.parity, Parity::Even);
+    }
+
+    #[test]
+    fn serial_config_round_trips() {
+        extern crate std;
+        use std::string::ToString;
 
-        let value = buad.parse::<i32>()?;
-        return Self::try_from(value);
+        for baud in BaudRate::ALL {
+            for parity in [Parity::None, Parity::Even, Parity::Odd] {
+                let config = SerialConfig { baud, parity };
+                let text = config.to_string();
+                assert_eq!(SerialConfig::try_from(text.as_str()).unwrap(), config);
+            }
+        }
     }
 }
@@ -0,0 +1,74 @@
+//! Values the module reports that vary in length, stored inline up to a
+//! fixed bound so they can live in [`Parameters`](super::Parameters).
+
+use super::ParseError;
+use core::str::from_utf8;
+
+/// Up to `N` bytes.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Bounded<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Bounded<N> {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for Bounded<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_bytes(), f)
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for Bounded<N> {
+    type Error = ParseError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() > N {
+            return Err(ParseError::TooLong);
+        }
+
+        let mut buffer = [0; N];
+        buffer[..value.len()].copy_from_slice(value);
+        Ok(Self {
+            buffer,
+            len: value.len(),
+        })
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for Bounded<N> {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_bytes())
+    }
+}
+
+/// Up to `N` bytes of text.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct BoundedStr<const N: usize>(Bounded<N>);
+
+impl<const N: usize> BoundedStr<N> {
+    pub fn as_str(&self) -> &str {
+        // Only ever filled from a `&str`, so this cannot fail.
+        from_utf8(self.0.as_bytes()).unwrap_or_default()
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for BoundedStr<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for BoundedStr<N> {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Bounded::try_from(value).map(Self)
+    }
+}
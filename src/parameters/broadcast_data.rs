@@ -0,0 +1,7 @@
+use super::bounded::Bounded;
+
+/// Longest broadcast data kept.
+pub const BROADCAST_DATA_MAX: usize = 32;
+
+/// The data a broadcaster advertises, as `AT+AVDA` takes and reports it.
+pub type BroadcastData = Bounded<BROADCAST_DATA_MAX>;
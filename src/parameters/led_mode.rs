@@ -0,0 +1,61 @@
+use super::{line_value, ParseError};
+
+pub const LED_ON: &str = "On";
+pub const LED_OFF: &str = "Off";
+
+/// Whether the status LED shows the connection state, set with `AT+LED`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LedMode {
+    /// Blinks while disconnected and stays lit while connected. The factory
+    /// setting.
+    On,
+    /// Always dark.
+    Off,
+}
+
+impl LedMode {
+    /// The digit `AT+LED=` takes.
+    pub fn code(self) -> u8 {
+        match self {
+            LedMode::On => b'1',
+            LedMode::Off => b'0',
+        }
+    }
+}
+
+impl TryFrom<&str> for LedMode {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            LED_ON => Ok(LedMode::On),
+            LED_OFF => Ok(LedMode::Off),
+            _ => Err(ParseError::WrongValue),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for LedMode {
+    type Error = ParseError;
+    // LED :On or LED:On
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        line_value(value, "LED", |value| Self::try_from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_led_line() {
+        assert_eq!(LedMode::try_from(&b"LED:On\r\n"[..]).unwrap(), LedMode::On);
+        assert_eq!(
+            LedMode::try_from(&b"LED:Off\r\n"[..]).unwrap(),
+            LedMode::Off
+        );
+        assert_eq!(LedMode::try_from(&b"LED :On\r\n"[..]).unwrap(), LedMode::On);
+        assert!(LedMode::try_from(&b"LED:Dim\r\n"[..]).is_err());
+        assert!(LedMode::try_from(&b"LEDS:On\r\n"[..]).is_err());
+    }
+}
@@ -1,16 +1,111 @@
 pub mod addr;
 pub mod baudrate;
+pub mod bounded;
+pub mod broadcast_data;
 pub mod connectable;
+pub mod led_mode;
+pub mod name;
+pub mod power_mode;
+pub mod rf_power;
 pub mod role;
 pub mod uuid;
+pub mod version;
 
 use core::{num::ParseIntError, str::Utf8Error};
 
+use crate::response::{Lexer, Token};
+
+/// Everything `AT+RX` reports.
+///
+/// Firmware revisions differ in which lines they print after the address, so
+/// those fields are optional.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Parameters {
+    pub name: name::Name,
     pub role: role::Role,
-    pub baud_rate: baudrate::BaudRate,
+    pub serial_config: baudrate::SerialConfig,
     pub addr: addr::Addr,
+    pub rf_power: Option<rf_power::RfPower>,
+    pub connectable: Option<connectable::IsConnectable>,
+    pub connect_uuid: Option<uuid::UUID>,
+    pub service_uuid: Option<uuid::UUID>,
+    pub characteristic_uuid: Option<uuid::UUID>,
+    pub led_mode: Option<led_mode::LedMode>,
+}
+
+/// Builds [`Parameters`] from `AT+RX` output one `Key:value` line at a time.
+///
+/// Lines may come in any order, end in `\r\n` or `\n`, and unknown keys are
+/// skipped.
+#[derive(Debug, Default)]
+pub struct ParametersParser {
+    name: Option<name::Name>,
+    role: Option<role::Role>,
+    serial_config: Option<baudrate::SerialConfig>,
+    addr: Option<addr::Addr>,
+    rf_power: Option<rf_power::RfPower>,
+    connectable: Option<connectable::IsConnectable>,
+    connect_uuid: Option<uuid::UUID>,
+    service_uuid: Option<uuid::UUID>,
+    characteristic_uuid: Option<uuid::UUID>,
+    led_mode: Option<led_mode::LedMode>,
+}
+
+impl ParametersParser {
+    pub fn feed_line(&mut self, line: &[u8]) -> Result<(), ParseError> {
+        field(line, |key, value| self.feed_field(key, value))?;
+        Ok(())
+    }
+
+    /// Takes a line already split into its key and value, as
+    /// [`Token::Field`] carries it.
+    pub fn feed_field(&mut self, key: &str, value: &str) -> Result<(), ParseError> {
+        match key {
+            "Name" => self.name = Some(name::Name::try_from(value)?),
+            "Role" => self.role = Some(role::Role::try_from(value)?),
+            "Baud" => self.serial_config = Some(baudrate::SerialConfig::try_from(value)?),
+            "Addr" => self.addr = Some(addr::Addr::try_from(value)?),
+            "RFPM" => self.rf_power = Some(rf_power::RfPower::try_from(value)?),
+            "Cont" => {
+                self.connectable = Some(connectable::IsConnectable::try_from(value.as_bytes())?)
+            }
+            "LUUID" => self.connect_uuid = Some(uuid::UUID::try_from(value.as_bytes())?),
+            "SUUID" => self.service_uuid = Some(uuid::UUID::try_from(value.as_bytes())?),
+            "TUUID" => self.characteristic_uuid = Some(uuid::UUID::try_from(value.as_bytes())?),
+            "LED" => self.led_mode = Some(led_mode::LedMode::try_from(value)?),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<Parameters, ParseError> {
+        Ok(Parameters {
+            name: self.name.ok_or(ParseError::MissingValue)?,
+            role: self.role.ok_or(ParseError::MissingValue)?,
+            serial_config: self.serial_config.ok_or(ParseError::MissingValue)?,
+            addr: self.addr.ok_or(ParseError::MissingValue)?,
+            rf_power: self.rf_power,
+            connectable: self.connectable,
+            connect_uuid: self.connect_uuid,
+            service_uuid: self.service_uuid,
+            characteristic_uuid: self.characteristic_uuid,
+            led_mode: self.led_mode,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for Parameters {
+    type Error = ParseError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut parser = ParametersParser::default();
+        for line in value.split(|&b| b == b'\n') {
+            parser.feed_line(line)?;
+        }
+
+        parser.finish()
+    }
 }
 
 #[derive(Debug)]
@@ -18,10 +113,61 @@ pub enum ParseError {
     PrefixError,
     WithoutNewline,
     WrongValue,
+    /// An argument lies outside the range the module accepts, so the
+    /// command is not encoded.
+    OutOfRange,
+    /// A required line was not reported.
+    MissingValue,
+    /// A value does not fit the buffer it is parsed into.
+    TooLong,
     Uft8Error(Utf8Error),
     ParseIntError(ParseIntError),
 }
 
+/// Reads one `Key:value` line, with or without its `\n`, as a
+/// [`Token::Field`] and hands its key and value to `take`. Blank lines give
+/// `None`.
+fn field<T>(
+    line: &[u8],
+    take: impl FnOnce(&str, &str) -> Result<T, ParseError>,
+) -> Result<Option<T>, ParseError> {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let mut lexer = Lexer::new();
+    if line.iter().any(|&byte| lexer.push(byte).is_some()) {
+        return Err(ParseError::WrongValue);
+    }
+
+    match lexer.push(b'\n') {
+        None => Ok(None),
+        Some(line) => match line?.token()? {
+            Token::Field { key, value } => take(key, value).map(Some),
+            _ => Err(ParseError::PrefixError),
+        },
+    }
+}
+
+/// Parses the value of a `Key:value\r\n` line with `parse`, after checking
+/// the key is `key`. Padding before the colon (`LED :On`) is allowed, as in
+/// [`ParametersParser::feed_line`].
+pub(crate) fn line_value<T>(
+    line: &[u8],
+    key: &str,
+    parse: impl FnOnce(&str) -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    if !line.ends_with(b"\n") {
+        return Err(ParseError::WithoutNewline);
+    }
+
+    let value = field(line, |k, value| {
+        if k == key {
+            parse(value)
+        } else {
+            Err(ParseError::PrefixError)
+        }
+    })?;
+    value.ok_or(ParseError::MissingValue)
+}
+
 impl From<Utf8Error> for ParseError {
     fn from(err: Utf8Error) -> Self {
         Self::Uft8Error(err)
@@ -33,3 +179,73 @@ impl From<ParseIntError> for ParseError {
         Self::ParseIntError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use addr::Addr;
+    use baudrate::{BaudRate, Parity};
+    use connectable::IsConnectable;
+    use led_mode::LedMode;
+    use rf_power::RfPower;
+    use role::Role;
+    use uuid::UUID;
+
+    const FULL: &[u8] = b"Name:HC-08\r\nRole:Slave\r\nBaud:9600,NONE\r\n\
+Addr:48,87,2D,12,34,56\r\nRFPM:4dBm\r\nCont:Connectable\r\n\
+LUUID:FFF0\r\nSUUID:FFE0\r\nTUUID:FFE1\r\n";
+
+    const SHORT: &[u8] = b"Name:beacon-01\r\nRole:Master\r\nBaud:115200,NONE\r\n\
+Addr:48:87:2D:AB:CD:EF\r\n";
+
+    #[test]
+    fn parse_full_transcript() {
+        let params = Parameters::try_from(FULL).unwrap();
+        assert_eq!(params.name.as_str(), "HC-08");
+        assert_eq!(params.role, Role::Slave);
+        assert_eq!(params.serial_config.baud, BaudRate::Bps9600);
+        assert_eq!(params.serial_config.parity, Parity::None);
+        assert_eq!(params.addr, Addr::try_from("48,87,2D,12,34,56").unwrap());
+        assert_eq!(params.rf_power, Some(RfPower::Dbm4));
+        assert_eq!(params.connectable, Some(IsConnectable(true)));
+        assert_eq!(params.connect_uuid, Some(UUID(0xFFF0)));
+        assert_eq!(params.service_uuid, Some(UUID(0xFFE0)));
+        assert_eq!(params.characteristic_uuid, Some(UUID(0xFFE1)));
+    }
+
+    #[test]
+    fn parse_short_transcript() {
+        let params = Parameters::try_from(SHORT).unwrap();
+        assert_eq!(params.name.as_str(), "beacon-01");
+        assert_eq!(params.role, Role::Master);
+        assert_eq!(params.serial_config.baud, BaudRate::Bps115200);
+        assert_eq!(params.rf_power, None);
+        assert_eq!(params.connectable, None);
+        assert_eq!(params.led_mode, None);
+    }
+
+    #[test]
+    fn lines_in_any_order_and_unknown_keys() {
+        let transcript = b"PIN :000000\nLED :Off\nRFPM:-6dBm\nCont:Non-Connectable\n\
+Addr:48,87,2D,12,34,56\nBaud:9600,NONE\nRole:Slave\nName:HC-08\n";
+        let params = Parameters::try_from(&transcript[..]).unwrap();
+        assert_eq!(params.role, Role::Slave);
+        assert_eq!(params.rf_power, Some(RfPower::DbmMinus6));
+        assert_eq!(params.connectable, Some(IsConnectable(false)));
+        assert_eq!(params.led_mode, Some(LedMode::Off));
+    }
+
+    #[test]
+    fn missing_or_malformed_lines_are_errors() {
+        assert!(matches!(
+            Parameters::try_from(&b"Name:HC-08\r\nRole:Slave\r\n"[..]),
+            Err(ParseError::MissingValue)
+        ));
+        assert!(matches!(
+            Parameters::try_from(&b"Name:a-name-well-over-twenty-bytes\r\n"[..]),
+            Err(ParseError::TooLong)
+        ));
+        assert!(Parameters::try_from(&b"garbage\r\n"[..]).is_err());
+        assert!(Parameters::try_from(&b"RFPM:lots\r\n"[..]).is_err());
+    }
+}
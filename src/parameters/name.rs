@@ -0,0 +1,27 @@
+use super::bounded::BoundedStr;
+
+/// Longest device name the module reports.
+pub const NAME_MAX: usize = 20;
+
+/// A device name.
+pub type Name = BoundedStr<NAME_MAX>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::ParseError;
+
+    #[test]
+    fn name_round_trip() {
+        assert_eq!(Name::try_from("HC-08").unwrap().as_str(), "HC-08");
+        assert_eq!(Name::try_from("").unwrap().as_str(), "");
+    }
+
+    #[test]
+    fn long_name_is_an_error() {
+        assert!(matches!(
+            Name::try_from("a-name-well-over-twenty-bytes"),
+            Err(ParseError::TooLong)
+        ));
+    }
+}
@@ -0,0 +1,57 @@
+use super::ParseError;
+
+/// How the module trades responsiveness for current draw, set with
+/// `AT+MODE`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PowerMode {
+    /// Always awake. The factory setting.
+    FullSpeed,
+    /// Stays reachable over the air, but advertises and listens less often.
+    LowPower1,
+    /// Sleeps once the auto-sleep time has passed without serial traffic,
+    /// and only wakes on incoming serial data.
+    LowPower2,
+}
+
+impl PowerMode {
+    /// The digit `AT+MODE=` takes and reports.
+    pub fn code(self) -> u8 {
+        match self {
+            PowerMode::FullSpeed => b'0',
+            PowerMode::LowPower1 => b'1',
+            PowerMode::LowPower2 => b'2',
+        }
+    }
+}
+
+impl TryFrom<&str> for PowerMode {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "0" => Ok(PowerMode::FullSpeed),
+            "1" => Ok(PowerMode::LowPower1),
+            "2" => Ok(PowerMode::LowPower2),
+            _ => Err(ParseError::WrongValue),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for mode in [
+            PowerMode::FullSpeed,
+            PowerMode::LowPower1,
+            PowerMode::LowPower2,
+        ] {
+            let code = [mode.code()];
+            let code = core::str::from_utf8(&code).unwrap();
+            assert_eq!(PowerMode::try_from(code).unwrap(), mode);
+        }
+        assert!(PowerMode::try_from("3").is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use super::{line_value, ParseError};
+
+/// Transmit power levels the module supports.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RfPower {
+    /// 4 dBm, the factory setting.
+    Dbm4,
+    /// 0 dBm.
+    Dbm0,
+    /// -6 dBm.
+    DbmMinus6,
+    /// -23 dBm.
+    DbmMinus23,
+}
+
+impl RfPower {
+    pub fn dbm(self) -> i8 {
+        match self {
+            RfPower::Dbm4 => 4,
+            RfPower::Dbm0 => 0,
+            RfPower::DbmMinus6 => -6,
+            RfPower::DbmMinus23 => -23,
+        }
+    }
+
+    /// The digit `AT+RFPM=` takes.
+    pub fn code(self) -> u8 {
+        match self {
+            RfPower::Dbm4 => b'0',
+            RfPower::Dbm0 => b'1',
+            RfPower::DbmMinus6 => b'2',
+            RfPower::DbmMinus23 => b'3',
+        }
+    }
+}
+
+impl TryFrom<i8> for RfPower {
+    type Error = ParseError;
+
+    fn try_from(dbm: i8) -> Result<Self, Self::Error> {
+        match dbm {
+            4 => Ok(RfPower::Dbm4),
+            0 => Ok(RfPower::Dbm0),
+            -6 => Ok(RfPower::DbmMinus6),
+            -23 => Ok(RfPower::DbmMinus23),
+            _ => Err(ParseError::WrongValue),
+        }
+    }
+}
+
+impl TryFrom<&str> for RfPower {
+    type Error = ParseError;
+    // 4dBm, +4dBm, -23dBm
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.strip_suffix("dBm").ok_or(ParseError::WrongValue)?;
+        Self::try_from(value.trim().parse::<i8>()?)
+    }
+}
+
+impl TryFrom<&[u8]> for RfPower {
+    type Error = ParseError;
+    // RFPM:4dBm
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        line_value(value, "RFPM", |value| Self::try_from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rf_power_line() {
+        assert_eq!(
+            RfPower::try_from(&b"RFPM:4dBm\r\n"[..]).unwrap(),
+            RfPower::Dbm4
+        );
+        assert_eq!(
+            RfPower::try_from(&b"RFPM:-23dBm\r\n"[..]).unwrap(),
+            RfPower::DbmMinus23
+        );
+        assert_eq!(RfPower::try_from("+4dBm").unwrap(), RfPower::Dbm4);
+    }
+
+    #[test]
+    fn unsupported_levels_are_errors() {
+        for value in ["", "4", "dBm", "3dBm", "-24dBm", "999dBm"] {
+            assert!(RfPower::try_from(value).is_err());
+        }
+    }
+}
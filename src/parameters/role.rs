@@ -1,5 +1,4 @@
-use super::ParseError;
-use core::str::from_utf8;
+use super::{line_value, ParseError};
 
 pub const MASTER: &str = "Master";
 pub const SLAVE: &str = "Slave";
@@ -26,15 +25,34 @@ impl TryFrom<&[u8]> for Role {
     type Error = ParseError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        if &value[0..5] != b"Role:" {
-            return Err(ParseError::PrefixError);
-        }
+        line_value(value, "Role", |value| Role::try_from(value))
+    }
+}
 
-        if &value[value.len() - 2..value.len()] != b"\r\n" {
-            return Err(ParseError::WithoutNewline);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_role_line() {
+        assert_eq!(Role::try_from(&b"Role:Slave\r\n"[..]).unwrap(), Role::Slave);
+        assert_eq!(
+            Role::try_from(&b"Role:Master\r\n"[..]).unwrap(),
+            Role::Master
+        );
+    }
 
-        let s = from_utf8(&value[5..value.len() - 2])?;
-        Role::try_from(s)
+    #[test]
+    fn malformed_role_line_is_an_error() {
+        for line in [
+            &b""[..],
+            b"Ro",
+            b"Role:",
+            b"Role:\r\n",
+            b"Role:Slave",
+            b"\r\n",
+        ] {
+            assert!(Role::try_from(line).is_err());
+        }
     }
 }
@@ -1,15 +1,19 @@
 use super::ParseError;
-use core::{char::from_digit, str::from_utf8};
+use core::fmt;
+use core::str::from_utf8;
 
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// A 16-bit Bluetooth UUID, written by the module as four hex digits.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub struct UUID(pub u32);
+pub struct UUID(pub u16);
 
-impl Into<[u8; 4]> for UUID {
-    fn into(mut self) -> [u8; 4] {
+impl From<UUID> for [u8; 4] {
+    /// Most significant digit first, as the module expects.
+    fn from(uuid: UUID) -> Self {
         let mut result = [0; 4];
-        for i in 0..4 {
-            result[i] = from_digit(self.0 % 16, 16).unwrap() as u8;
-            self.0 >>= 4;
+        for (i, v) in result.iter_mut().enumerate() {
+            *v = HEX_DIGITS[((uuid.0 >> (12 - 4 * i)) & 0xF) as usize];
         }
 
         result
@@ -20,10 +24,163 @@ impl TryFrom<&[u8]> for UUID {
     type Error = ParseError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        if value.len() > 4 {
+        if value.len() > 4 || !value.iter().all(u8::is_ascii_hexdigit) {
             Err(ParseError::WrongValue)
         } else {
-            Ok(UUID(u32::from_str_radix(from_utf8(&value)?, 16)?))
+            Ok(UUID(u16::from_str_radix(from_utf8(value)?, 16)?))
+        }
+    }
+}
+
+impl fmt::Display for UUID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04X}", self.0)
+    }
+}
+
+/// A full 128-bit UUID, most significant byte first.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct UUID128(pub [u8; 16]);
+
+impl UUID128 {
+    /// `00000000-0000-1000-8000-00805F9B34FB`, which 16-bit UUIDs are
+    /// shorthand for.
+    pub const BASE: UUID128 = UUID128([
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5F, 0x9B, 0x34,
+        0xFB,
+    ]);
+
+    /// The 16-bit form, if this UUID is derived from [`UUID128::BASE`].
+    pub fn to_short(self) -> Option<UUID> {
+        let mut base = self;
+        base.0[2] = 0;
+        base.0[3] = 0;
+        if base == Self::BASE {
+            Some(UUID(u16::from_be_bytes([self.0[2], self.0[3]])))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<UUID> for UUID128 {
+    /// Expands a 16-bit UUID against [`UUID128::BASE`].
+    fn from(uuid: UUID) -> Self {
+        let mut result = Self::BASE;
+        result.0[2..4].copy_from_slice(&uuid.0.to_be_bytes());
+        result
+    }
+}
+
+/// Byte offsets of the dashes in the canonical form.
+const DASHES: [usize; 4] = [8, 13, 18, 23];
+
+impl TryFrom<&str> for UUID128 {
+    type Error = ParseError;
+    // 0000FFE0-0000-1000-8000-00805F9B34FB
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.as_bytes();
+        if value.len() != 36 || DASHES.iter().any(|&i| value[i] != b'-') {
+            return Err(ParseError::WrongValue);
+        }
+
+        let mut result = [0u8; 16];
+        let mut digits = value.iter().filter(|&&b| b != b'-');
+        for byte in result.iter_mut() {
+            let (Some(&hi), Some(&lo)) = (digits.next(), digits.next()) else {
+                return Err(ParseError::WrongValue);
+            };
+            *byte = hex_value(hi)? << 4 | hex_value(lo)?;
+        }
+
+        Ok(UUID128(result))
+    }
+}
+
+impl fmt::Display for UUID128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02X}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn hex_value(digit: u8) -> Result<u8, ParseError> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => Err(ParseError::WrongValue),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::string::ToString;
+
+    #[test]
+    fn malformed_uuid_is_an_error() {
+        for value in [&b""[..], b"FFE0F", b"FFG0", b"+", b"+FF", b"\xff\xfe"] {
+            assert!(UUID::try_from(value).is_err());
+        }
+    }
+
+    #[test]
+    fn uuid_is_written_most_significant_digit_first() {
+        assert_eq!(<[u8; 4]>::from(UUID(0xFFE0)), *b"FFE0");
+        assert_eq!(<[u8; 4]>::from(UUID(0x00A1)), *b"00A1");
+        assert_eq!(UUID(0x1234).to_string(), "1234");
+    }
+
+    #[test]
+    fn uuid_round_trips() {
+        for uuid in [0, 0x1, 0x1234, 0xFFE0, 0xFFFF].map(UUID) {
+            let digits: [u8; 4] = uuid.into();
+            assert_eq!(UUID::try_from(&digits[..]).unwrap(), uuid);
+        }
+    }
+
+    #[test]
+    fn uuid128_canonical_form() {
+        let text = "0000FFE0-0000-1000-8000-00805F9B34FB";
+        let uuid = UUID128::try_from(text).unwrap();
+        assert_eq!(uuid, UUID128::from(UUID(0xFFE0)));
+        assert_eq!(uuid.to_string(), text);
+        assert_eq!(
+            UUID128::try_from(text.to_lowercase().as_str()).unwrap(),
+            uuid
+        );
+    }
+
+    #[test]
+    fn uuid128_shortens_only_base_uuids() {
+        assert_eq!(UUID128::from(UUID(0xFFE1)).to_short(), Some(UUID(0xFFE1)));
+
+        let custom = UUID128::try_from("6E400001-B5A3-F393-E0A9-E50E24DCCA9E").unwrap();
+        assert_eq!(custom.to_short(), None);
+        assert_eq!(custom.to_string(), "6E400001-B5A3-F393-E0A9-E50E24DCCA9E");
+    }
+
+    #[test]
+    fn malformed_uuid128_is_an_error() {
+        for value in [
+            "",
+            "0000FFE0000010008000-00805F9B34FB",
+            "0000FFE0-0000-1000-8000-00805F9B34F",
+            "0000FFE0-0000-1000-8000-00805F9B34FBB",
+            "0000FFE0-0000-1000-8000_00805F9B34FB",
+            "0000FFG0-0000-1000-8000-00805F9B34FB",
+            "0000FFE0-0000-1000-8000-00805F9B34+B",
+        ] {
+            assert!(UUID128::try_from(value).is_err());
         }
     }
 }
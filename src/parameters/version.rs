@@ -0,0 +1,7 @@
+use super::bounded::BoundedStr;
+
+/// Longest firmware version string kept.
+pub const VERSION_MAX: usize = 32;
+
+/// The firmware version `AT+VERSION` reports, e.g. `HC-08 V3.1,2017-07-07`.
+pub type Version = BoundedStr<VERSION_MAX>;
@@ -0,0 +1,388 @@
+//! Splitting the module's answers into tokens.
+//!
+//! The module answers in a handful of shapes: a bare `OK`, an echo such as
+//! `OK+AINT=160`, `Key:value\r\n` lines from `AT+RX`, `ERROR`, and bare
+//! values like `Master`. Some firmware ends them with `\r\n`, some with
+//! nothing at all, in which case only the module going quiet ends them.
+//! [`Lexer`] takes bytes as they arrive and yields [`Line`]s without doing
+//! any I/O itself, so the blocking and async drivers share it.
+//!
+//! Which shape a line has depends on the command it answers: a name may
+//! well be `OK` or contain a `:`. So a line is only read as a [`Token`]
+//! when the command asks for it.
+
+use core::str::from_utf8;
+
+use crate::command::AtCommand;
+use crate::parameters::ParseError;
+use crate::Error;
+
+/// Longest token kept; anything past it is reported as too long.
+pub const LINE_MAX: usize = 32;
+
+/// Most bytes one answer may take before it is cut off as too long, so a
+/// module that never goes quiet cannot hold a command forever. `AT+RX`, the
+/// longest answer, is about a dozen lines.
+pub const RESPONSE_MAX: usize = LINE_MAX * 16;
+
+/// One line of an answer, as it arrived.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Line<'a> {
+    bytes: &'a [u8],
+    /// Ended by `\n` rather than by the module going quiet.
+    terminated: bool,
+}
+
+impl<'a> Line<'a> {
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn as_str(&self) -> Result<&'a str, ParseError> {
+        Ok(from_utf8(self.bytes)?)
+    }
+
+    /// Whether the line is the module refusing a command.
+    pub fn is_error(&self) -> bool {
+        self.bytes.starts_with(b"ERROR")
+    }
+
+    /// Reads the line as one of the shapes most answers take.
+    pub fn token(&self) -> Result<Token<'a>, ParseError> {
+        Ok(Token::new(self.as_str()?, self.terminated))
+    }
+}
+
+/// One complete piece of an answer, as [`Line::token`] reads it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Token<'a> {
+    /// `OK` on its own.
+    Ok,
+    /// `OK+KEY=value`, the echo setters with a value answer with.
+    Reply { key: &'a str, value: &'a str },
+    /// A `Key:value` line, as `AT+RX` prints them. The key is trimmed, since
+    /// some firmware pads it (`LED :On`).
+    Field { key: &'a str, value: &'a str },
+    /// `ERROR`: the module refused the command.
+    Error(&'a str),
+    /// A bare value, e.g. `Master` in answer to `AT+ROLE=?`.
+    Value(&'a str),
+}
+
+impl<'a> Token<'a> {
+    fn new(text: &'a str, terminated: bool) -> Self {
+        if text == "OK" {
+            Token::Ok
+        } else if let Some(reply) = text.strip_prefix("OK+") {
+            let (key, value) = reply.split_once('=').unwrap_or((reply, ""));
+            Token::Reply { key, value }
+        } else if text.starts_with("ERROR") {
+            Token::Error(text)
+        } else if let (true, Some((key, value))) = (terminated, text.split_once(':')) {
+            Token::Field {
+                key: key.trim_end(),
+                value,
+            }
+        } else {
+            Token::Value(text)
+        }
+    }
+}
+
+/// Splits bytes from the module into [`Line`]s.
+///
+/// A line ends at `\n` ([`push`](Self::push) returns it) or when the module
+/// goes quiet ([`finish`](Self::finish) returns it). A trailing `\r` is
+/// dropped and blank lines are skipped.
+#[derive(Debug, Default)]
+pub struct Lexer {
+    line: [u8; LINE_MAX],
+    len: usize,
+    /// The line ran past [`LINE_MAX`]; it is reported once it ends.
+    overflow: bool,
+    /// The last call ended a line, so the next byte starts a new one.
+    done: bool,
+}
+
+impl Lexer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds one byte, returning the line it ends, if any.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Line<'_>, ParseError>> {
+        self.start();
+        if byte == b'\n' {
+            return self.end(true);
+        }
+
+        match self.line.get_mut(self.len) {
+            Some(v) => {
+                *v = byte;
+                self.len += 1;
+            }
+            None => self.overflow = true,
+        }
+
+        None
+    }
+
+    /// Ends whatever has arrived since the last line, as the module going
+    /// quiet does.
+    pub fn finish(&mut self) -> Option<Result<Line<'_>, ParseError>> {
+        self.start();
+        self.end(false)
+    }
+
+    fn start(&mut self) {
+        if self.done {
+            self.len = 0;
+            self.overflow = false;
+            self.done = false;
+        }
+    }
+
+    fn end(&mut self, terminated: bool) -> Option<Result<Line<'_>, ParseError>> {
+        self.done = true;
+        if self.overflow {
+            return Some(Err(ParseError::TooLong));
+        }
+
+        let line = &self.line[..self.len];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            return None;
+        }
+
+        Some(Ok(Line {
+            bytes: line,
+            terminated,
+        }))
+    }
+}
+
+/// Feeds one command's answer through a [`Lexer`] into
+/// [`AtCommand::decode`].
+pub(crate) struct ResponseReader<'c, A: AtCommand> {
+    lexer: Lexer,
+    decoding: Decoding<'c, A>,
+    received: usize,
+}
+
+/// Where an answer's decoding stands, kept apart from the [`Lexer`] whose
+/// tokens it takes.
+struct Decoding<'c, A: AtCommand> {
+    command: &'c A,
+    partial: A::Partial,
+    rejected: bool,
+    parsed: Result<(), ParseError>,
+}
+
+impl<'c, A: AtCommand> ResponseReader<'c, A> {
+    pub(crate) fn new(command: &'c A) -> Self {
+        Self {
+            lexer: Lexer::new(),
+            decoding: Decoding {
+                command,
+                partial: Default::default(),
+                rejected: false,
+                parsed: Ok(()),
+            },
+            received: 0,
+        }
+    }
+
+    /// Whether any byte of the answer has arrived yet.
+    pub(crate) fn received(&self) -> bool {
+        self.received > 0
+    }
+
+    /// Takes one byte of the answer. Fails once the answer runs past
+    /// [`RESPONSE_MAX`].
+    pub(crate) fn push(&mut self, byte: u8) -> Result<(), ParseError> {
+        self.received += 1;
+        if self.received > RESPONSE_MAX {
+            return Err(ParseError::TooLong);
+        }
+
+        self.decoding.take(self.lexer.push(byte));
+        Ok(())
+    }
+
+    /// Completes the answer once the module has gone quiet.
+    pub(crate) fn finish<E, W>(mut self) -> Result<A::Response, Error<E, W>> {
+        self.decoding.take(self.lexer.finish());
+
+        let Decoding {
+            command,
+            partial,
+            rejected,
+            parsed,
+        } = self.decoding;
+        if rejected {
+            return Err(Error::Rejected);
+        }
+        parsed?;
+        Ok(command.finish(partial)?)
+    }
+}
+
+impl<A: AtCommand> Decoding<'_, A> {
+    fn take(&mut self, line: Option<Result<Line<'_>, ParseError>>) {
+        // After the first bad line the rest of the answer is still read but
+        // ignored, so the next command starts clean.
+        if self.rejected || self.parsed.is_err() {
+            return;
+        }
+
+        self.parsed = match line {
+            Some(Ok(line))
+                if line.is_error() && (!A::BARE_VALUE || line.as_bytes() == b"ERROR") =>
+            {
+                self.rejected = true;
+                Ok(())
+            }
+            Some(Ok(line)) => self.command.decode(&mut self.partial, line),
+            Some(Err(e)) => Err(e),
+            None => Ok(()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pushes `input`, checking that nothing before its last byte ends a
+    /// line, and reads what the last byte ends as a token.
+    fn push_all<'l>(lexer: &'l mut Lexer, input: &[u8]) -> Option<Result<Token<'l>, ParseError>> {
+        let (last, rest) = input.split_last().unwrap();
+        for &byte in rest {
+            assert!(lexer.push(byte).is_none());
+        }
+        lexer.push(*last).map(|line| line?.token())
+    }
+
+    /// Pushes `input` and reads what the module going quiet ends as a
+    /// token.
+    fn quiet<'l>(lexer: &'l mut Lexer, input: &[u8]) -> Option<Result<Token<'l>, ParseError>> {
+        for &byte in input {
+            assert!(lexer.push(byte).is_none());
+        }
+        lexer.finish().map(|line| line?.token())
+    }
+
+    #[test]
+    fn bare_answers_end_when_the_module_goes_quiet() {
+        let mut lexer = Lexer::new();
+        assert_eq!(quiet(&mut lexer, b"OK").unwrap().unwrap(), Token::Ok);
+        assert_eq!(
+            quiet(&mut lexer, b"Master").unwrap().unwrap(),
+            Token::Value("Master")
+        );
+        assert!(quiet(&mut lexer, b"").is_none());
+    }
+
+    #[test]
+    fn line_endings_are_optional() {
+        let mut lexer = Lexer::new();
+        assert_eq!(push_all(&mut lexer, b"OK\r\n").unwrap().unwrap(), Token::Ok);
+        assert!(lexer.finish().is_none());
+        assert_eq!(
+            push_all(&mut lexer, b"Master\r\n").unwrap().unwrap(),
+            Token::Value("Master")
+        );
+        assert_eq!(
+            quiet(&mut lexer, b"Master\r").unwrap().unwrap(),
+            Token::Value("Master")
+        );
+    }
+
+    #[test]
+    fn replies_split_key_and_value() {
+        let mut lexer = Lexer::new();
+        assert_eq!(
+            quiet(&mut lexer, b"OK+LUUID=FFE0").unwrap().unwrap(),
+            Token::Reply {
+                key: "LUUID",
+                value: "FFE0"
+            }
+        );
+        assert_eq!(
+            push_all(&mut lexer, b"OK+CINT=16,32\r\n").unwrap().unwrap(),
+            Token::Reply {
+                key: "CINT",
+                value: "16,32"
+            }
+        );
+    }
+
+    #[test]
+    fn fields_need_a_line_ending() {
+        let mut lexer = Lexer::new();
+        assert!(push_all(&mut lexer, b"\r\n").is_none());
+        assert_eq!(
+            push_all(&mut lexer, b"LED :On\r\n").unwrap().unwrap(),
+            Token::Field {
+                key: "LED",
+                value: "On"
+            }
+        );
+        assert_eq!(
+            push_all(&mut lexer, b"Addr:48:87:2D:12:34:56\n")
+                .unwrap()
+                .unwrap(),
+            Token::Field {
+                key: "Addr",
+                value: "48:87:2D:12:34:56"
+            }
+        );
+        assert_eq!(
+            quiet(&mut lexer, b"a:b").unwrap().unwrap(),
+            Token::Value("a:b")
+        );
+    }
+
+    #[test]
+    fn lines_keep_what_arrived() {
+        let mut lexer = Lexer::new();
+        for &byte in b"\xffName:OK" {
+            assert!(lexer.push(byte).is_none());
+        }
+        let line = lexer.push(b'\n').unwrap().unwrap();
+        assert_eq!(line.as_bytes(), b"\xffName:OK");
+        assert!(line.as_str().is_err());
+        assert!(!line.is_error());
+    }
+
+    #[test]
+    fn errors_are_tokens() {
+        let mut lexer = Lexer::new();
+        assert_eq!(
+            quiet(&mut lexer, b"ERROR").unwrap().unwrap(),
+            Token::Error("ERROR")
+        );
+    }
+
+    #[test]
+    fn bad_tokens_do_not_swallow_the_next() {
+        let mut lexer = Lexer::new();
+        for _ in 0..LINE_MAX + 5 {
+            assert!(lexer.push(b'a').is_none());
+        }
+        assert!(matches!(lexer.push(b'\n'), Some(Err(ParseError::TooLong))));
+        assert_eq!(
+            push_all(&mut lexer, b"Role:Slave\r\n").unwrap().unwrap(),
+            Token::Field {
+                key: "Role",
+                value: "Slave"
+            }
+        );
+
+        assert!(matches!(
+            push_all(&mut lexer, b"\xff\xfe\n"),
+            Some(Err(ParseError::Uft8Error(_)))
+        ));
+        assert_eq!(quiet(&mut lexer, b"OK").unwrap().unwrap(), Token::Ok);
+    }
+}
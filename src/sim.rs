@@ -0,0 +1,484 @@
+//! In-memory HC-08 for host-side tests.
+//!
+//! [`Hc08Sim`] implements the embedded-hal serial traits and answers the AT
+//! command set the way the module's firmware does, so [`Hc08`](crate::Hc08)
+//! and code built on it can be exercised without hardware. A command is
+//! executed as soon as the host starts reading its response. Clones share the
+//! same module, so one clone can be handed to the driver while another
+//! inspects [`Hc08Sim::state`].
+
+extern crate std;
+
+use core::cell::RefCell;
+use core::convert::Infallible;
+use std::collections::VecDeque;
+use std::format;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::hal::Reconfigure;
+use crate::parameters::addr::Addr;
+use crate::parameters::baudrate::{BaudRate, Parity};
+use crate::parameters::led_mode::{LedMode, LED_OFF, LED_ON};
+use crate::parameters::power_mode::PowerMode;
+use crate::parameters::rf_power::RfPower;
+use crate::parameters::role::Role;
+use crate::LINE_GAP_MS;
+
+pub const VERSION: &str = "HC-08 V3.1,2017-07-07";
+
+/// Printed by the module once it has booted after `AT+RESET`.
+pub const BOOT_BANNER: &str = "\r\nHC-08 V3.1,2017-07-07\r\n";
+
+/// How many reads go unanswered while the module reboots, unless set with
+/// [`Hc08Sim::set_boot_reads`]: with [`NoDelay`] the driver polls every
+/// millisecond, so this is twice a [`LINE_GAP_MS`] of silence. Bytes sent
+/// meanwhile are lost.
+const BOOT_READS: u32 = 2 * LINE_GAP_MS;
+
+/// Everything the module persists across commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimState {
+    pub name: String,
+    pub role: Role,
+    pub connectable: bool,
+    pub baud_rate: BaudRate,
+    pub parity: Parity,
+    pub addr: Addr,
+    pub rf_power: RfPower,
+    pub power_mode: PowerMode,
+    pub led_mode: LedMode,
+    /// Idle time before sleeping in [`PowerMode::LowPower2`], in seconds.
+    pub auto_sleep: u32,
+    pub connect_uuid: u16,
+    pub service_uuid: u16,
+    pub characteristic_uuid: u16,
+    pub broadcast_data: Vec<u8>,
+    /// Minimum and maximum connection interval, in 1.25 ms units.
+    pub connect_internal: (u32, u32),
+    /// Advertising interval, in 0.625 ms units.
+    pub advertising_interval: u32,
+    /// Connection supervision timeout, in 10 ms units.
+    pub connect_timeout: u32,
+}
+
+impl Default for SimState {
+    /// Factory settings, as restored by `AT+DEFAULT`.
+    fn default() -> Self {
+        Self {
+            name: String::from("HC-08"),
+            role: Role::Master,
+            connectable: true,
+            baud_rate: BaudRate::Bps9600,
+            parity: Parity::None,
+            addr: Addr::new([0x48, 0x87, 0x2D, 0x12, 0x34, 0x56]),
+            rf_power: RfPower::Dbm4,
+            power_mode: PowerMode::FullSpeed,
+            led_mode: LedMode::On,
+            auto_sleep: 1,
+            connect_uuid: 0xFFF0,
+            service_uuid: 0xFFE0,
+            characteristic_uuid: 0xFFE1,
+            broadcast_data: Vec::new(),
+            connect_internal: (8, 16),
+            advertising_interval: 160,
+            connect_timeout: 400,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: SimState,
+    /// UART settings of the host end of the line. Bytes sent while they
+    /// differ from the module's are lost.
+    host: (BaudRate, Parity),
+    /// Sleeping in [`PowerMode::LowPower2`]. The next byte received wakes
+    /// the module and is lost.
+    asleep: bool,
+    /// Reads left until the module has rebooted.
+    booting: u32,
+    /// What `booting` starts from after `AT+RESET`.
+    boot_reads: u32,
+    /// Bytes of the command currently being received.
+    command: Vec<u8>,
+    /// Bytes waiting to be read by the host.
+    response: VecDeque<u8>,
+    /// Every command received so far.
+    history: Vec<Vec<u8>>,
+    /// Acknowledge commands without applying them, like firmware that
+    /// silently drops values it does not accept.
+    ignore_changes: bool,
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Self {
+            state: SimState::default(),
+            host: (BaudRate::Bps9600, Parity::None),
+            asleep: false,
+            booting: 0,
+            boot_reads: BOOT_READS,
+            command: Vec::new(),
+            response: VecDeque::new(),
+            history: Vec::new(),
+            ignore_changes: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Hc08Sim {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl Hc08Sim {
+    /// A module with factory settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_state(state: SimState) -> Self {
+        let sim = Self::default();
+        sim.inner.borrow_mut().state = state;
+        sim
+    }
+
+    pub fn state(&self) -> SimState {
+        self.inner.borrow().state.clone()
+    }
+
+    /// Makes the module acknowledge every command as usual but keep its
+    /// current state.
+    pub fn set_ignore_changes(&self, ignore: bool) {
+        self.inner.borrow_mut().ignore_changes = ignore;
+    }
+
+    /// Sets how many reads go unanswered while the module reboots. With 0
+    /// the boot banner follows the `OK` straight away.
+    pub fn set_boot_reads(&self, reads: u32) {
+        self.inner.borrow_mut().boot_reads = reads;
+    }
+
+    /// Every command the host has sent, oldest first.
+    pub fn history(&self) -> Vec<Vec<u8>> {
+        self.inner.borrow().history.clone()
+    }
+
+    /// The UART settings the host end of the line is using.
+    pub fn host_settings(&self) -> (BaudRate, Parity) {
+        self.inner.borrow().host
+    }
+
+    /// Lets the auto-sleep time pass. Only a module in
+    /// [`PowerMode::LowPower2`] goes to sleep.
+    pub fn fall_asleep(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.asleep = inner.state.power_mode == PowerMode::LowPower2;
+    }
+
+    pub fn is_asleep(&self) -> bool {
+        self.inner.borrow().asleep
+    }
+
+    fn read(&self) -> Option<u8> {
+        let mut inner = self.inner.borrow_mut();
+        if inner.asleep {
+            return None;
+        }
+        if inner.booting > 0 && inner.response.is_empty() {
+            inner.booting -= 1;
+            if inner.booting == 0 {
+                inner.response.extend(BOOT_BANNER.bytes());
+            }
+            return None;
+        }
+        if !inner.command.is_empty() {
+            let command = core::mem::take(&mut inner.command);
+            let saved = inner.ignore_changes.then(|| inner.state.clone());
+            let response = inner.state.execute(&command);
+            if let Some(saved) = saved {
+                inner.state = saved;
+            }
+            inner.response.extend(response);
+            if command == b"AT+RESET" {
+                if inner.boot_reads == 0 {
+                    inner.response.extend(BOOT_BANNER.bytes());
+                }
+                inner.booting = inner.boot_reads;
+            }
+            inner.history.push(command);
+        }
+
+        inner.response.pop_front()
+    }
+
+    fn write(&self, byte: u8) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.asleep {
+            inner.asleep = false;
+        } else if inner.booting > 0 {
+            // Lost while rebooting.
+        } else if inner.host == (inner.state.baud_rate, inner.state.parity) {
+            inner.command.push(byte);
+        }
+    }
+}
+
+fn parse_uuid(value: &[u8]) -> Option<u16> {
+    if value.len() != 4 {
+        return None;
+    }
+    u16::from_str_radix(core::str::from_utf8(value).ok()?, 16).ok()
+}
+
+fn parse_number(value: &[u8]) -> Option<u32> {
+    core::str::from_utf8(value).ok()?.parse().ok()
+}
+
+impl SimState {
+    fn params(&self) -> String {
+        let [a0, a1, a2, a3, a4, a5] = self.addr.bytes();
+        let mut params = String::new();
+        params += &format!("Name:{}\r\n", self.name);
+        params += &format!("Role:{}\r\n", role_name(self.role));
+        params += &format!("Baud:{},{}\r\n", self.baud_rate as u32, self.parity.name());
+        params += &format!(
+            "Addr:{:02X},{:02X},{:02X},{:02X},{:02X},{:02X}\r\n",
+            a0, a1, a2, a3, a4, a5
+        );
+        params += &format!("RFPM:{}dBm\r\n", self.rf_power.dbm());
+        params += &format!("Cont:{}\r\n", connectable_name(self.connectable));
+        params += &format!("LUUID:{:04X}\r\n", self.connect_uuid);
+        params += &format!("SUUID:{:04X}\r\n", self.service_uuid);
+        params += &format!("TUUID:{:04X}\r\n", self.characteristic_uuid);
+        params += &format!("LED :{}\r\n", led_name(self.led_mode));
+        params
+    }
+
+    fn set_uuid(uuid: &mut u16, prefix: &str, value: &[u8]) -> Vec<u8> {
+        match parse_uuid(value) {
+            Some(v) => {
+                *uuid = v;
+                let mut response = Vec::from(prefix.as_bytes());
+                response.extend_from_slice(value);
+                response
+            }
+            None => Vec::from(&b"ERROR"[..]),
+        }
+    }
+
+    fn execute(&mut self, command: &[u8]) -> Vec<u8> {
+        let response: String = match command {
+            b"AT" => "OK".into(),
+            b"AT+DEFAULT" => {
+                *self = SimState::default();
+                "OK".into()
+            }
+            b"AT+RX" => self.params(),
+            b"AT+VERSION" => VERSION.into(),
+            b"AT+CLEAR" => "OK".into(),
+            b"AT+RESET" => "OK".into(),
+            b"AT+ROLE=?" => role_name(self.role).into(),
+            b"AT+ROLE=M" => {
+                self.role = Role::Master;
+                "OK".into()
+            }
+            b"AT+ROLE=S" => {
+                self.role = Role::Slave;
+                "OK".into()
+            }
+            b"AT+CONT=?" => connectable_name(self.connectable).into(),
+            b"AT+CONT=0" => {
+                self.connectable = true;
+                "OK".into()
+            }
+            b"AT+CONT=1" => {
+                self.connectable = false;
+                "OK".into()
+            }
+            b"AT+NAME=?" => self.name.clone(),
+            b"AT+AVDA=?" => return self.broadcast_data.clone(),
+            b"AT+AINT=?" => format!("OK+AINT={}", self.advertising_interval),
+            b"AT+LED=?" => led_name(self.led_mode).into(),
+            b"AT+LED=0" => {
+                self.led_mode = LedMode::Off;
+                "OK".into()
+            }
+            b"AT+LED=1" => {
+                self.led_mode = LedMode::On;
+                "OK".into()
+            }
+            b"AT+MODE=?" => String::from(self.power_mode.code() as char),
+            b"AT+RFPM=?" => format!("{}dBm", self.rf_power.dbm()),
+            b"AT+ADDR=?" => String::from_utf8_lossy(&self.addr.to_hex()).into(),
+            b"AT+BAUD=?" => format!("{},{}", self.baud_rate as u32, self.parity.name()),
+            b"AT+LUUID=?" => format!("OK+LUUID={:04X}", self.connect_uuid),
+            b"AT+SUUID=?" => format!("OK+SUUID={:04X}", self.service_uuid),
+            b"AT+TUUID=?" => format!("OK+TUUID={:04X}", self.characteristic_uuid),
+            _ => return self.execute_with_value(command),
+        };
+
+        response.into_bytes()
+    }
+
+    fn execute_with_value(&mut self, command: &[u8]) -> Vec<u8> {
+        if let Some(name) = command.strip_prefix(b"AT+NAME=") {
+            match core::str::from_utf8(name) {
+                Ok(name) if !name.is_empty() => {
+                    self.name = name.into();
+                    return Vec::from(&b"OK"[..]);
+                }
+                _ => {}
+            }
+        } else if let Some(data) = command.strip_prefix(b"AT+AVDA=") {
+            self.broadcast_data = data.into();
+            return Vec::from(&b"OK"[..]);
+        } else if let Some(value) = command.strip_prefix(b"AT+LUUID=") {
+            return Self::set_uuid(&mut self.connect_uuid, "OK+LUUID=", value);
+        } else if let Some(value) = command.strip_prefix(b"AT+SUUID=") {
+            return Self::set_uuid(&mut self.service_uuid, "OK+SUUID=", value);
+        } else if let Some(value) = command.strip_prefix(b"AT+TUUID=") {
+            return Self::set_uuid(&mut self.characteristic_uuid, "OK+TUUID=", value);
+        } else if let Some(value) = command.strip_prefix(b"AT+MODE=") {
+            let mode = match value {
+                b"0" => Some(PowerMode::FullSpeed),
+                b"1" => Some(PowerMode::LowPower1),
+                b"2" => Some(PowerMode::LowPower2),
+                _ => None,
+            };
+            if let Some(mode) = mode {
+                self.power_mode = mode;
+                return Vec::from(&b"OK"[..]);
+            }
+        } else if let Some(value) = command.strip_prefix(b"AT+AUST=") {
+            match parse_number(value) {
+                Some(seconds) if (1..=300).contains(&seconds) => {
+                    self.auto_sleep = seconds;
+                    return Vec::from(&b"OK"[..]);
+                }
+                _ => {}
+            }
+        } else if let Some(value) = command.strip_prefix(b"AT+RFPM=") {
+            let power = match value {
+                b"0" => Some(RfPower::Dbm4),
+                b"1" => Some(RfPower::Dbm0),
+                b"2" => Some(RfPower::DbmMinus6),
+                b"3" => Some(RfPower::DbmMinus23),
+                _ => None,
+            };
+            if let Some(power) = power {
+                self.rf_power = power;
+                return Vec::from(&b"OK"[..]);
+            }
+        } else if let Some(value) = command.strip_prefix(b"AT+ADDR=") {
+            let addr = core::str::from_utf8(value).ok().map(Addr::try_from);
+            if let (12, Some(Ok(addr))) = (value.len(), addr) {
+                self.addr = addr;
+                return Vec::from(&b"OK"[..]);
+            }
+        } else if let Some(value) = command.strip_prefix(b"AT+BAUD=") {
+            if let [rate @ .., b',', parity] = value {
+                let rate = parse_number(rate).and_then(|r| BaudRate::try_from(r as i32).ok());
+                let parity = match parity {
+                    b'N' => Some(Parity::None),
+                    b'E' => Some(Parity::Even),
+                    b'O' => Some(Parity::Odd),
+                    _ => None,
+                };
+                if let (Some(rate), Some(parity)) = (rate, parity) {
+                    self.baud_rate = rate;
+                    self.parity = parity;
+                    return Vec::from(&b"OK"[..]);
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(b"AT+CINT=") {
+            let mut parts = value.splitn(2, |&b| b == b',');
+            let min = parts.next().and_then(parse_number);
+            let max = parts.next().map_or(min, parse_number);
+            if let (Some(min), Some(max)) = (min, max) {
+                if (6..=3200).contains(&min) && (min..=3200).contains(&max) {
+                    self.connect_internal = (min, max);
+                    return format!("OK+CINT={},{}", min, max).into_bytes();
+                }
+            }
+        } else if let Some(value) = command.strip_prefix(b"AT+AINT=") {
+            match parse_number(value) {
+                Some(units) if (32..=16384).contains(&units) => {
+                    self.advertising_interval = units;
+                    return format!("OK+AINT={}", units).into_bytes();
+                }
+                _ => {}
+            }
+        } else if let Some(value) = command.strip_prefix(b"AT+CTOUT=") {
+            match parse_number(value) {
+                Some(time) if (10..=3200).contains(&time) => {
+                    self.connect_timeout = time;
+                    return format!("OK+CTOUT={}", time).into_bytes();
+                }
+                _ => {}
+            }
+        }
+
+        Vec::from(&b"ERROR"[..])
+    }
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::Master => crate::parameters::role::MASTER,
+        Role::Slave => crate::parameters::role::SLAVE,
+    }
+}
+
+fn led_name(mode: LedMode) -> &'static str {
+    match mode {
+        LedMode::On => LED_ON,
+        LedMode::Off => LED_OFF,
+    }
+}
+
+fn connectable_name(connectable: bool) -> &'static str {
+    if connectable {
+        "Connectable"
+    } else {
+        "Non-Connectable"
+    }
+}
+
+impl embedded_hal::serial::Read<u8> for Hc08Sim {
+    type Error = Infallible;
+
+    fn read(&mut self) -> nb::Result<u8, Infallible> {
+        Hc08Sim::read(self).ok_or(nb::Error::WouldBlock)
+    }
+}
+
+impl embedded_hal::serial::Write<u8> for Hc08Sim {
+    type Error = Infallible;
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), Infallible> {
+        Hc08Sim::write(self, byte);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl Reconfigure for Hc08Sim {
+    fn reconfigure(&mut self, baud_rate: BaudRate, parity: Parity) -> Result<(), Infallible> {
+        self.inner.borrow_mut().host = (baud_rate, parity);
+        Ok(())
+    }
+}
+
+/// A delay that returns immediately, for use alongside [`Hc08Sim`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoDelay;
+
+impl embedded_hal::blocking::delay::DelayMs<u32> for NoDelay {
+    fn delay_ms(&mut self, _ms: u32) {}
+}
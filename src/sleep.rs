@@ -0,0 +1,59 @@
+//! Handles for a module that may be asleep.
+//!
+//! In [`PowerMode::LowPower2`](crate::parameters::power_mode::PowerMode) the
+//! module ignores AT commands until serial data wakes it. [`Sleeping`] holds
+//! the driver during that time, so no command can be sent until
+//! [`Sleeping::wake`] has seen the module answer again.
+
+use crate::hal::{Delay, Serial};
+use crate::Hc08;
+
+/// Sent to wake the module. It is lost, along with whatever else arrives
+/// while the module starts up.
+pub const WAKE_BYTES: &[u8] = b"AAAAAAAAAA";
+
+/// How long the module takes to accept commands after waking, in
+/// milliseconds.
+pub const WAKE_DELAY_MS: u32 = 100;
+
+/// How many times `AT` is tried after the wake bytes before giving up.
+pub const WAKE_ATTEMPTS: u32 = 5;
+
+pub struct Sleeping<S, D, R, C> {
+    hc08: Hc08<S, D, R, C>,
+}
+
+impl<S, D, R, C> Sleeping<S, D, R, C>
+where
+    S: Serial,
+    D: Delay,
+{
+    pub(crate) fn new(hc08: Hc08<S, D, R, C>) -> Self {
+        Self { hc08 }
+    }
+
+    /// Wakes the module and waits until it answers `AT`. On failure the
+    /// handle is returned still sleeping.
+    pub fn wake(mut self) -> Result<Hc08<S, D, R, C>, Self> {
+        let hc08 = &mut self.hc08;
+        if hc08.write_buffer(WAKE_BYTES).is_err() {
+            return Err(self);
+        }
+
+        for _ in 0..WAKE_ATTEMPTS {
+            hc08.delay.delay_ms(WAKE_DELAY_MS);
+            hc08.discard_input();
+            if hc08.is_ok() {
+                return Ok(self.hc08);
+            }
+        }
+
+        Err(self)
+    }
+
+    /// Gives up the driver, e.g. to reuse the serial port while the module
+    /// sleeps. Nothing is sent, so the module stays asleep.
+    pub fn release(self) -> (S, D) {
+        (self.hc08.serial, self.hc08.delay)
+    }
+}
@@ -0,0 +1,104 @@
+use hc_08::any::{AnyHc08, Mode};
+use hc_08::parameters::connectable::IsConnectable;
+use hc_08::parameters::role::Role;
+use hc_08::sim::{Hc08Sim, NoDelay, SimState};
+use hc_08::{Connectable, Error, Hc08, Master, NonConnectable, Slave};
+
+#[test]
+fn mode_is_chosen_at_runtime() {
+    let sim = Hc08Sim::new();
+    let hc08 = AnyHc08::from(Hc08::try_new(sim.clone(), NoDelay).unwrap());
+    assert_eq!(hc08.mode(), Mode::Central);
+
+    for mode in [
+        Mode::Broadcast,
+        Mode::Observer,
+        Mode::Peripheral,
+        Mode::Central,
+    ] {
+        let mut hc08 = AnyHc08::from(Hc08::try_new(sim.clone(), NoDelay).unwrap())
+            .into_mode(mode)
+            .ok()
+            .unwrap();
+        assert_eq!(hc08.mode(), mode);
+        assert_eq!(hc08.query_role().unwrap(), mode.role());
+        assert_eq!(hc08.query_connectable().unwrap(), mode.connectable());
+        assert_eq!(
+            Mode::new(sim.state().role, IsConnectable(sim.state().connectable)),
+            mode
+        );
+    }
+}
+
+#[test]
+fn mode_specific_commands_check_the_mode() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = AnyHc08::from(Hc08::try_new(sim.clone(), NoDelay).unwrap());
+
+    assert!(matches!(
+        hc08.change_broadcast_data(b"beacon"),
+        Err(Error::WrongMode)
+    ));
+    assert!(matches!(hc08.get_service_uuid(), Err(Error::WrongMode)));
+    hc08.clear_slave_addr().unwrap();
+
+    let mut hc08 = hc08.into_mode(Mode::Broadcast).ok().unwrap();
+    hc08.change_broadcast_data(b"beacon").unwrap();
+    assert_eq!(sim.state().broadcast_data, b"beacon");
+    assert!(matches!(
+        hc08.change_connect_timeout(1000),
+        Err(Error::WrongMode)
+    ));
+    assert!(matches!(hc08.clear_slave_addr(), Err(Error::WrongMode)));
+}
+
+#[test]
+fn conversions_to_typed_handles() {
+    let sim = Hc08Sim::new();
+    let hc08 = AnyHc08::from(Hc08::try_new(sim, NoDelay).unwrap());
+
+    let hc08 = Hc08::<_, _, Slave, Connectable>::try_from(hc08)
+        .err()
+        .unwrap();
+    let hc08 = Hc08::<_, _, Master, NonConnectable>::try_from(hc08)
+        .err()
+        .unwrap();
+    let mut hc08 = Hc08::<_, _, Master, Connectable>::try_from(hc08)
+        .ok()
+        .unwrap();
+    assert_eq!(hc08.query_role().unwrap(), Role::Master);
+}
+
+#[test]
+fn attach_keeps_the_module_configuration() {
+    let sim = Hc08Sim::with_state(SimState {
+        name: "beacon-01".into(),
+        role: Role::Slave,
+        connectable: false,
+        ..SimState::default()
+    });
+
+    let mut hc08 = Hc08::attach(sim.clone(), NoDelay).unwrap();
+    assert_eq!(hc08.mode(), Mode::Broadcast);
+    hc08.change_broadcast_data(b"beacon").unwrap();
+    assert_eq!(sim.state().name, "beacon-01");
+    assert!(!sim.history().iter().any(|cmd| cmd == b"AT+DEFAULT"));
+
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::attach(sim, NoDelay).unwrap();
+    assert_eq!(hc08.mode(), Mode::Central);
+}
+
+#[test]
+fn transitions_are_verified() {
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    sim.set_ignore_changes(true);
+    let mut hc08 = hc08.into_peripheral_mode().err().unwrap();
+    assert_eq!(hc08.query_mode().unwrap(), Mode::Central);
+
+    sim.set_ignore_changes(false);
+    let mut hc08 = hc08.into_peripheral_mode().ok().unwrap();
+    assert_eq!(hc08.query_mode().unwrap(), Mode::Peripheral);
+}
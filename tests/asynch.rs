@@ -0,0 +1,185 @@
+#![cfg(feature = "async")]
+
+use core::convert::Infallible;
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use embedded_hal::serial::{Read as _, Write as _};
+use embedded_hal_async::delay::DelayNs;
+use embedded_io_async::{ErrorType, Read, Write};
+
+use hc_08::asynch::AsyncHc08;
+use hc_08::hal::Reconfigure;
+use hc_08::parameters::baudrate::{BaudRate, Parity};
+use hc_08::parameters::role::Role;
+use hc_08::parameters::uuid::UUID;
+use hc_08::parameters::ParseError;
+use hc_08::sim::Hc08Sim;
+use hc_08::Error;
+
+/// Polls `fut` until it completes. Every future here is woken by being
+/// polled again, so no real waker is needed.
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// An async port on top of the simulator. A read stays pending until the
+/// module has a byte.
+#[derive(Debug)]
+struct AsyncPort(Hc08Sim);
+
+impl ErrorType for AsyncPort {
+    type Error = Infallible;
+}
+
+impl Read for AsyncPort {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        poll_fn(|_| match self.0.read() {
+            Ok(byte) => {
+                buf[0] = byte;
+                Poll::Ready(Ok(1))
+            }
+            Err(_) => Poll::Pending,
+        })
+        .await
+    }
+}
+
+impl Write for AsyncPort {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        for &byte in buf {
+            self.0.write(byte).unwrap();
+        }
+        Ok(buf.len())
+    }
+
+    async fn flush(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Lets a microsecond pass each time it is polled, so a timeout races the
+/// read the way it would against a real clock.
+#[derive(Debug)]
+struct PolledDelay;
+
+impl DelayNs for PolledDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        let mut left_us = ns.div_ceil(1000);
+        poll_fn(|_| {
+            if left_us == 0 {
+                return Poll::Ready(());
+            }
+            left_us -= 1;
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+#[test]
+fn commands_run_through_the_async_driver() {
+    let sim = Hc08Sim::new();
+    block_on(async {
+        let hc08 = AsyncHc08::try_new(AsyncPort(sim.clone()), PolledDelay)
+            .await
+            .unwrap();
+        let mut hc08 = hc08.into_peripheral_mode().await.ok().unwrap();
+        assert_eq!(hc08.query_role().await.unwrap(), Role::Slave);
+
+        hc08.change_name("sensor-1").await.unwrap();
+        let mut buffer = [0u8; 16];
+        assert_eq!(hc08.get_name(&mut buffer).await.unwrap(), "sensor-1");
+
+        hc08.set_service_uuid(UUID(0xFFA0)).await.unwrap();
+        assert_eq!(hc08.get_service_uuid().await.unwrap(), UUID(0xFFA0));
+        assert_eq!(hc08.get_parameters().await.unwrap().role, Role::Slave);
+    });
+    assert_eq!(sim.state().name, "sensor-1");
+}
+
+#[test]
+fn silent_module_times_out() {
+    let sim = Hc08Sim::new();
+    block_on(async {
+        let mut hc08 = AsyncHc08::try_new(AsyncPort(sim.clone()), PolledDelay)
+            .await
+            .unwrap();
+        hc08.set_timeout(10);
+        sim.clone()
+            .reconfigure(BaudRate::Bps115200, Parity::None)
+            .unwrap();
+        assert!(matches!(hc08.query_role().await, Err(Error::Timeout)));
+
+        // The cancelled read took nothing, so the link recovers.
+        sim.clone()
+            .reconfigure(BaudRate::Bps9600, Parity::None)
+            .unwrap();
+        assert!(hc08.is_ok().await);
+    });
+}
+
+/// Receives without end, like a module passing through a peer's data.
+#[derive(Debug)]
+struct EndlessPort;
+
+impl ErrorType for EndlessPort {
+    type Error = Infallible;
+}
+
+impl Read for EndlessPort {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        buf[0] = b'x';
+        Ok(1)
+    }
+}
+
+impl Write for EndlessPort {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        Ok(buf.len())
+    }
+
+    async fn flush(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[test]
+fn endless_answer_is_cut_off() {
+    assert!(matches!(
+        block_on(AsyncHc08::try_new(EndlessPort, PolledDelay)),
+        Err((Error::ParseError(ParseError::TooLong), _, _))
+    ));
+}
+
+#[test]
+fn verify_mode_reads_back_in_the_async_driver() {
+    let sim = Hc08Sim::new();
+    block_on(async {
+        let mut hc08 = AsyncHc08::try_new(AsyncPort(sim.clone()), PolledDelay)
+            .await
+            .unwrap();
+        hc08.set_verify(true);
+        hc08.change_role(Role::Slave).await.unwrap();
+
+        let sent = sim.history().len();
+        assert!(matches!(
+            hc08.change_name("a-name-well-over-twenty-bytes").await,
+            Err(Error::ParseError(ParseError::TooLong))
+        ));
+        assert_eq!(sim.history().len(), sent);
+
+        sim.set_ignore_changes(true);
+        assert!(matches!(
+            hc08.change_role(Role::Master).await,
+            Err(Error::VerifyMismatch { .. })
+        ));
+    });
+}
@@ -0,0 +1,142 @@
+use hc_08::any::{AnyHc08, Mode};
+use hc_08::config::Hc08Config;
+use hc_08::parameters::connectable::IsConnectable;
+use hc_08::parameters::led_mode::LedMode;
+use hc_08::parameters::rf_power::RfPower;
+use hc_08::parameters::role::Role;
+use hc_08::parameters::uuid::UUID;
+use hc_08::sim::{Hc08Sim, NoDelay};
+use hc_08::{Error, Hc08};
+
+fn peripheral_config() -> Hc08Config<'static> {
+    Hc08Config {
+        name: Some("sensor-1"),
+        role: Some(Role::Slave),
+        connectable: Some(IsConnectable(true)),
+        service_uuid: Some(UUID(0xFFA0)),
+        characteristic_uuid: Some(UUID(0xFFE1)),
+        advertising_interval_ms: Some(200),
+        rf_power: Some(RfPower::Dbm4),
+        led_mode: Some(LedMode::Off),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn apply_sends_only_what_differs() {
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    let (hc08, changed) = hc08.apply(&peripheral_config()).ok().unwrap();
+    assert!(matches!(hc08, AnyHc08::Peripheral(_)));
+    assert_eq!(
+        changed,
+        Hc08Config {
+            name: Some("sensor-1"),
+            role: Some(Role::Slave),
+            service_uuid: Some(UUID(0xFFA0)),
+            advertising_interval_ms: Some(200),
+            led_mode: Some(LedMode::Off),
+            ..Default::default()
+        }
+    );
+
+    let state = sim.state();
+    assert_eq!(state.name, "sensor-1");
+    assert_eq!(state.role, Role::Slave);
+    assert_eq!(state.service_uuid, 0xFFA0);
+    assert_eq!(state.advertising_interval, 320);
+    assert_eq!(state.led_mode, LedMode::Off);
+
+    let history = sim.history();
+    assert!(!history.iter().any(|c| c.as_slice() == b"AT+CONT=0"));
+    assert!(!history.iter().any(|c| c.starts_with(b"AT+TUUID=")));
+    assert!(!history
+        .iter()
+        .any(|c| c.starts_with(b"AT+RFPM=") && c != b"AT+RFPM=?"));
+}
+
+#[test]
+fn applying_twice_changes_nothing() {
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    let (hc08, _) = hc08.apply(&peripheral_config()).ok().unwrap();
+
+    let sent = sim.history().len();
+    let (hc08, changed) = hc08.apply(&peripheral_config()).ok().unwrap();
+    assert_eq!(hc08.mode(), Mode::Peripheral);
+    assert_eq!(changed, Hc08Config::default());
+    assert!(sim.history()[sent..]
+        .iter()
+        .all(|c| c == b"AT+RX" || c.ends_with(b"=?")));
+}
+
+#[test]
+fn bad_config_is_refused_before_sending() {
+    let sim = Hc08Sim::new();
+    let hc08 = AnyHc08::from(Hc08::try_new(sim.clone(), NoDelay).unwrap());
+    let sent = sim.history().len();
+
+    let config = Hc08Config {
+        role: Some(Role::Slave),
+        connect_uuid: Some(UUID(0xFFA0)),
+        ..Default::default()
+    };
+    let Err((Error::WrongMode, hc08)) = hc08.apply(&config) else {
+        panic!("central-only setting applied to a peripheral");
+    };
+    assert_eq!(hc08.mode(), Mode::Central);
+
+    // The handle comes back, so the module can still be configured.
+    let config = Hc08Config {
+        name: Some("sensor-1"),
+        connect_timeout_ms: Some(10),
+        ..Default::default()
+    };
+    let Err((Error::OutOfRange, hc08)) = hc08.apply(&config) else {
+        panic!("out of range timeout applied");
+    };
+
+    let history = sim.history();
+    assert!(history[sent..]
+        .iter()
+        .all(|c| c == b"AT+RX" || c == b"AT+CONT=?"));
+
+    let config = Hc08Config {
+        name: Some("sensor-1"),
+        ..Default::default()
+    };
+    hc08.apply(&config).ok().unwrap();
+    assert_eq!(sim.state().name, "sensor-1");
+}
+
+#[test]
+fn failed_apply_hands_back_the_mode_reached() {
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    // The role changes before the module refuses the empty name.
+    let config = Hc08Config {
+        role: Some(Role::Slave),
+        name: Some(""),
+        ..Default::default()
+    };
+    let Err((Error::Rejected, hc08)) = hc08.apply(&config) else {
+        panic!("empty name applied");
+    };
+    assert_eq!(sim.state().role, Role::Slave);
+    assert_eq!(hc08.mode(), Mode::Peripheral);
+
+    // A change the module acknowledges but drops leaves it where it was.
+    let mut hc08 = hc08;
+    hc08.set_verify(true);
+    sim.set_ignore_changes(true);
+    let config = Hc08Config {
+        connectable: Some(IsConnectable(false)),
+        ..Default::default()
+    };
+    let Err((Error::VerifyMismatch { .. }, hc08)) = hc08.apply(&config) else {
+        panic!("dropped change verified");
+    };
+    assert_eq!(hc08.mode(), Mode::Peripheral);
+}
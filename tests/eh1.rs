@@ -0,0 +1,122 @@
+#![cfg(feature = "eh1")]
+
+use core::convert::Infallible;
+
+use embedded_hal::serial::{Read as _, Write as _};
+use embedded_hal_1::delay::DelayNs;
+use embedded_io::{ErrorType, Read, ReadReady, Write};
+
+use hc_08::hal::{IoSerial, NsDelay, Reconfigure, SetBaud};
+use hc_08::parameters::baudrate::{BaudRate, Parity};
+use hc_08::parameters::role::Role;
+use hc_08::sim::Hc08Sim;
+use hc_08::{Error, Hc08};
+
+/// An embedded-io port on top of the simulator, holding back one byte so
+/// `read_ready` can answer without losing it.
+#[derive(Debug)]
+struct IoPort {
+    sim: Hc08Sim,
+    pending: Option<u8>,
+}
+
+impl IoPort {
+    fn new(sim: Hc08Sim) -> Self {
+        Self { sim, pending: None }
+    }
+}
+
+impl ErrorType for IoPort {
+    type Error = Infallible;
+}
+
+impl ReadReady for IoPort {
+    fn read_ready(&mut self) -> Result<bool, Infallible> {
+        if self.pending.is_none() {
+            self.pending = self.sim.read().ok();
+        }
+        Ok(self.pending.is_some())
+    }
+}
+
+impl Read for IoPort {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        // Blocks until a byte is there, as embedded-io requires.
+        while !self.read_ready()? {}
+        buf[0] = self.pending.take().unwrap();
+        Ok(1)
+    }
+}
+
+impl Write for IoPort {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        for &byte in buf {
+            self.sim.write(byte).unwrap();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl SetBaud for IoPort {
+    fn set_baud(&mut self, baud_rate: BaudRate, parity: Parity) -> Result<(), Infallible> {
+        self.pending = None;
+        self.sim.reconfigure(baud_rate, parity)
+    }
+}
+
+#[derive(Debug)]
+struct NoNsDelay;
+
+impl DelayNs for NoNsDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[test]
+fn commands_run_over_embedded_io() {
+    let sim = Hc08Sim::new();
+    let serial = IoSerial::new(IoPort::new(sim.clone()));
+    let hc08 = Hc08::try_new(serial, NsDelay::new(NoNsDelay)).unwrap();
+    let mut hc08 = hc08.into_peripheral_mode().ok().unwrap();
+
+    hc08.change_name("sensor-1").unwrap();
+    let mut buffer = [0u8; 16];
+    assert_eq!(hc08.get_name(&mut buffer).unwrap(), "sensor-1");
+    assert_eq!(hc08.query_role().unwrap(), Role::Slave);
+    assert_eq!(sim.state().name, "sensor-1");
+}
+
+#[test]
+fn silent_port_times_out() {
+    let sim = Hc08Sim::new();
+    let serial = IoSerial::new(IoPort::new(sim.clone()));
+    let mut hc08 = Hc08::try_new(serial, NsDelay::new(NoNsDelay)).unwrap();
+    hc08.set_timeout(10);
+    sim.clone()
+        .reconfigure(BaudRate::Bps115200, Parity::None)
+        .unwrap();
+
+    assert!(matches!(hc08.query_role(), Err(Error::Timeout)));
+}
+
+#[test]
+fn baud_rate_changes_over_embedded_io() {
+    let sim = Hc08Sim::new();
+    let serial = IoSerial::new(IoPort::new(sim.clone()));
+    let mut hc08 = Hc08::try_new(serial, NsDelay::new(NoNsDelay)).unwrap();
+
+    hc08.change_baud_rate(BaudRate::Bps115200, Parity::None)
+        .unwrap();
+    assert_eq!(sim.state().baud_rate, BaudRate::Bps115200);
+    assert!(hc08.is_ok());
+
+    let mut port = IoPort::new(sim.clone());
+    port.set_baud(BaudRate::Bps9600, Parity::None).unwrap();
+    let Ok((baud_rate, _)) = Hc08::probe(IoSerial::new(port), NsDelay::new(NoNsDelay)) else {
+        panic!("module not found");
+    };
+    assert_eq!(baud_rate, BaudRate::Bps115200);
+}
@@ -0,0 +1,712 @@
+use hc_08::any::Mode;
+use hc_08::command::{AtCommand, ChangeConnectTimeout, ChangeName, QueryRfPower, Setting};
+use hc_08::hal::{Reconfigure, UsDelay};
+use hc_08::parameters::addr::Addr;
+use hc_08::parameters::baudrate::{BaudRate, Parity, SerialConfig};
+use hc_08::parameters::connectable::IsConnectable;
+use hc_08::parameters::led_mode::LedMode;
+use hc_08::parameters::power_mode::PowerMode;
+use hc_08::parameters::rf_power::RfPower;
+use hc_08::parameters::role::Role;
+use hc_08::parameters::uuid::UUID;
+use hc_08::parameters::version::Version;
+use hc_08::parameters::ParseError;
+use hc_08::response::Line;
+use hc_08::sim::{Hc08Sim, NoDelay, SimState, VERSION};
+use hc_08::{Error, Hc08, DEFAULT_TIMEOUT_MS, LINE_GAP_MS, POLL_INTERVAL_US};
+
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
+use std::cell::Cell;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+#[test]
+fn try_new_restores_factory_settings() {
+    let sim = Hc08Sim::with_state(SimState {
+        name: "bench".into(),
+        role: Role::Slave,
+        ..SimState::default()
+    });
+
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    assert!(hc08.is_ok());
+    assert_eq!(sim.state(), SimState::default());
+    assert_eq!(sim.history()[0], b"AT+DEFAULT");
+}
+
+#[test]
+fn mode_transitions_reach_the_module() {
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    let mut hc08 = hc08.into_broadcast_mode().ok().unwrap();
+    assert_eq!(hc08.query_role().unwrap(), Role::Slave);
+    assert_eq!(hc08.query_connectable().unwrap(), IsConnectable(false));
+    hc08.change_broadcast_data(b"beacon").unwrap();
+    assert_eq!(sim.state().broadcast_data, b"beacon");
+
+    let mut hc08 = hc08.into_central_mode().ok().unwrap();
+    assert_eq!(hc08.query_role().unwrap(), Role::Master);
+    assert_eq!(hc08.query_connectable().unwrap(), IsConnectable(true));
+    hc08.clear_slave_addr().unwrap();
+}
+
+#[test]
+fn name_and_version() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    hc08.change_name("sensor-1").unwrap();
+    assert_eq!(sim.state().name, "sensor-1");
+
+    let mut buffer = [0u8; 8];
+    assert_eq!(hc08.get_name(&mut buffer).unwrap(), "sensor-1");
+
+    let mut buffer = [0u8; 21];
+    assert_eq!(hc08.get_version(&mut buffer).unwrap(), VERSION);
+}
+
+#[test]
+fn names_that_look_like_answers_read_back() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    let mut buffer = [0u8; 20];
+    for name in ["OK", "ERROR-1", "OK+NAME=x", "Key:value"] {
+        hc08.change_name(name).unwrap();
+        assert_eq!(hc08.get_name(&mut buffer).unwrap(), name);
+    }
+}
+
+#[test]
+fn get_parameters_reports_module_state() {
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::try_new(sim, NoDelay).unwrap();
+    let mut hc08 = hc08.into_peripheral_mode().ok().unwrap();
+
+    hc08.change_name("sensor-1").unwrap();
+
+    let params = hc08.get_parameters().unwrap();
+    assert_eq!(params.name.as_str(), "sensor-1");
+    assert_eq!(params.role, Role::Slave);
+    assert_eq!(params.serial_config, SerialConfig::default());
+    assert_eq!(params.rf_power, Some(RfPower::Dbm4));
+    assert_eq!(params.connectable, Some(IsConnectable(true)));
+    assert_eq!(params.connect_uuid, Some(UUID(0xFFF0)));
+    assert_eq!(params.service_uuid, Some(UUID(0xFFE0)));
+    assert_eq!(params.characteristic_uuid, Some(UUID(0xFFE1)));
+
+    // The whole response was consumed, so the link is still in sync.
+    assert!(hc08.is_ok());
+}
+
+#[test]
+fn get_parameters_rejects_long_lines_without_desyncing() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim, NoDelay).unwrap();
+    hc08.change_name("a-name-well-over-thirty-two-bytes")
+        .unwrap();
+
+    assert!(matches!(
+        hc08.get_parameters(),
+        Err(Error::ParseError(ParseError::TooLong))
+    ));
+    assert!(hc08.is_ok());
+}
+
+#[test]
+fn connection_parameters() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    hc08.change_connect_internal(20, 40).unwrap();
+    assert_eq!(sim.state().connect_internal, (16, 32));
+    hc08.change_connect_timeout(6000).unwrap();
+    assert_eq!(sim.state().connect_timeout, 600);
+
+    assert!(matches!(
+        hc08.change_connect_internal(40, 20),
+        Err(Error::OutOfRange)
+    ));
+    assert!(matches!(
+        hc08.change_connect_timeout(50),
+        Err(Error::OutOfRange)
+    ));
+
+    // Commands check their own range, so sending one directly is refused
+    // the same way, before anything reaches the module.
+    let sent = sim.history().len();
+    assert!(matches!(
+        hc08.execute(&ChangeConnectTimeout(5)),
+        Err(Error::OutOfRange)
+    ));
+    assert_eq!(sim.history().len(), sent);
+    assert_eq!(sim.state().connect_timeout, 600);
+}
+
+#[test]
+fn uuid_queries() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim, NoDelay).unwrap();
+    assert_eq!(hc08.query_connect_uuid().unwrap(), UUID(0xFFF0));
+
+    let mut hc08 = hc08.into_peripheral_mode().ok().unwrap();
+    assert_eq!(hc08.get_service_uuid().unwrap(), UUID(0xFFE0));
+    assert_eq!(hc08.get_characteristic_uuid().unwrap(), UUID(0xFFE1));
+}
+
+#[test]
+fn uuids_round_trip_through_the_module() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    hc08.set_connect_uuid(UUID(0xFFF1)).unwrap();
+    assert_eq!(sim.state().connect_uuid, 0xFFF1);
+    assert_eq!(hc08.query_connect_uuid().unwrap(), UUID(0xFFF1));
+
+    let mut hc08 = hc08.into_peripheral_mode().ok().unwrap();
+    hc08.set_service_uuid(UUID(0x1234)).unwrap();
+    hc08.set_characteristic_uuid(UUID(0x00A1)).unwrap();
+    assert_eq!(sim.state().service_uuid, 0x1234);
+    assert_eq!(hc08.get_service_uuid().unwrap(), UUID(0x1234));
+    assert_eq!(hc08.get_characteristic_uuid().unwrap(), UUID(0x00A1));
+}
+
+#[test]
+fn silent_module_times_out() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    hc08.set_timeout(10);
+    sim.clone()
+        .reconfigure(BaudRate::Bps115200, Parity::None)
+        .unwrap();
+
+    let mut buffer = [0u8; 16];
+    assert!(matches!(hc08.get_name(&mut buffer), Err(Error::Timeout)));
+}
+
+/// A port whose receiver reports framing errors while its transmitter cannot
+/// fail, as on many embedded-hal 0.2 HALs.
+#[derive(Debug)]
+struct FramingPort {
+    sim: Hc08Sim,
+    broken: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct Framing;
+
+impl embedded_hal::serial::Read<u8> for FramingPort {
+    type Error = Framing;
+
+    fn read(&mut self) -> nb::Result<u8, Framing> {
+        if self.broken {
+            return Err(nb::Error::Other(Framing));
+        }
+        embedded_hal::serial::Read::read(&mut self.sim).map_err(|_| nb::Error::WouldBlock)
+    }
+}
+
+impl embedded_hal::serial::Write<u8> for FramingPort {
+    type Error = Infallible;
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), Infallible> {
+        embedded_hal::serial::Write::write(&mut self.sim, byte)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[test]
+fn read_and_write_errors_may_differ() {
+    let port = FramingPort {
+        sim: Hc08Sim::new(),
+        broken: false,
+    };
+    let mut hc08 = Hc08::try_new(port, NoDelay).unwrap();
+    assert_eq!(hc08.query_role().unwrap(), Role::Master);
+
+    let port = FramingPort {
+        sim: Hc08Sim::new(),
+        broken: true,
+    };
+    let (error, port, _): (Error<Framing, Infallible>, _, _) =
+        Hc08::try_new(port, NoDelay).err().unwrap();
+    assert!(matches!(error, Error::Read(Framing)));
+    assert!(port.broken);
+}
+
+/// A port that never stops receiving, like a module passing through data
+/// from a connected peer.
+#[derive(Debug)]
+struct Endless;
+
+impl embedded_hal::serial::Read<u8> for Endless {
+    type Error = Infallible;
+
+    fn read(&mut self) -> nb::Result<u8, Infallible> {
+        Ok(b'x')
+    }
+}
+
+impl embedded_hal::serial::Write<u8> for Endless {
+    type Error = Infallible;
+
+    fn write(&mut self, _byte: u8) -> nb::Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[test]
+fn endless_answer_is_cut_off() {
+    assert!(matches!(
+        Hc08::try_new(Endless, NoDelay),
+        Err((Error::ParseError(ParseError::TooLong), _, _))
+    ));
+}
+
+/// Adds up the time the driver asks to wait, and the longest single wait.
+#[derive(Debug, Clone, Default)]
+struct MeasuredDelay {
+    total_us: Rc<Cell<u64>>,
+    longest_us: Rc<Cell<u64>>,
+}
+
+impl MeasuredDelay {
+    fn wait(&self, us: u64) {
+        self.total_us.set(self.total_us.get() + us);
+        self.longest_us.set(self.longest_us.get().max(us));
+    }
+}
+
+impl DelayMs<u32> for MeasuredDelay {
+    fn delay_ms(&mut self, ms: u32) {
+        self.wait(u64::from(ms) * 1000);
+    }
+}
+
+impl DelayUs<u32> for MeasuredDelay {
+    fn delay_us(&mut self, us: u32) {
+        self.wait(us.into());
+    }
+}
+
+/// Only waits whole milliseconds, like many embedded-hal 0.2 timers.
+#[derive(Debug, Clone, Default)]
+struct MsDelay(MeasuredDelay);
+
+impl DelayMs<u32> for MsDelay {
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms);
+    }
+}
+
+#[test]
+fn millisecond_delays_poll_every_millisecond() {
+    let sim = Hc08Sim::new();
+    let delay = MsDelay::default();
+    let mut hc08 = Hc08::try_new(sim.clone(), delay.clone()).unwrap();
+    hc08.set_timeout(10);
+    sim.clone()
+        .reconfigure(BaudRate::Bps115200, Parity::None)
+        .unwrap();
+
+    delay.0.total_us.set(0);
+    assert!(!hc08.is_ok());
+    assert_eq!(delay.0.total_us.get(), 10_000);
+    assert_eq!(delay.0.longest_us.get(), 1000);
+}
+
+#[test]
+fn reads_poll_well_within_a_byte_time() {
+    let sim = Hc08Sim::new();
+    let delay = MeasuredDelay::default();
+    let mut hc08 = Hc08::try_new(sim.clone(), UsDelay::new(delay.clone())).unwrap();
+    hc08.set_timeout(10);
+    sim.clone()
+        .reconfigure(BaudRate::Bps115200, Parity::None)
+        .unwrap();
+
+    delay.total_us.set(0);
+    assert!(!hc08.is_ok());
+    assert_eq!(delay.total_us.get(), 10_000);
+    assert_eq!(delay.longest_us.get(), u64::from(POLL_INTERVAL_US));
+}
+
+#[test]
+fn change_baud_rate_moves_the_host_along() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    hc08.change_baud_rate(BaudRate::Bps115200, Parity::Even)
+        .unwrap();
+    assert_eq!(sim.state().baud_rate, BaudRate::Bps115200);
+    assert_eq!(sim.state().parity, Parity::Even);
+    assert_eq!(sim.host_settings(), (BaudRate::Bps115200, Parity::Even));
+    assert_eq!(sim.history()[1], b"AT+BAUD=115200,E");
+    assert_eq!(
+        hc08.get_parameters().unwrap().serial_config,
+        SerialConfig {
+            baud: BaudRate::Bps115200,
+            parity: Parity::Even,
+        }
+    );
+}
+
+#[test]
+fn mismatched_baud_rate_is_silent() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    hc08.set_timeout(10);
+
+    sim.clone()
+        .reconfigure(BaudRate::Bps19200, Parity::None)
+        .unwrap();
+    assert!(!hc08.is_ok());
+}
+
+#[test]
+fn probe_finds_the_module_baud_rate() {
+    let sim = Hc08Sim::with_state(SimState {
+        role: Role::Slave,
+        baud_rate: BaudRate::Bps38400,
+        ..SimState::default()
+    });
+
+    let (baud_rate, mut hc08) = Hc08::probe(sim.clone(), NoDelay).unwrap();
+    assert_eq!(baud_rate, BaudRate::Bps38400);
+    assert_eq!(hc08.mode(), Mode::Peripheral);
+    assert_eq!(hc08.timeout(), DEFAULT_TIMEOUT_MS);
+    assert_eq!(sim.host_settings(), (BaudRate::Bps38400, Parity::None));
+    assert!(hc08.is_ok());
+}
+
+#[test]
+fn probe_gives_up_on_a_module_with_parity() {
+    let sim = Hc08Sim::with_state(SimState {
+        parity: Parity::Odd,
+        ..SimState::default()
+    });
+
+    let Err((error, serial, delay)) = Hc08::probe(sim.clone(), NoDelay) else {
+        panic!("a module with parity answered the probe");
+    };
+    assert!(matches!(error, Error::Timeout));
+    assert!(sim.history().is_empty());
+
+    // The port comes back, so the caller can fall back to another setup.
+    let mut serial = serial;
+    serial.reconfigure(BaudRate::Bps9600, Parity::Odd).unwrap();
+    assert!(Hc08::attach(serial, delay).is_ok());
+}
+
+#[test]
+fn try_new_hands_the_port_back_on_failure() {
+    let sim = Hc08Sim::with_state(SimState {
+        baud_rate: BaudRate::Bps115200,
+        ..SimState::default()
+    });
+
+    let Err((error, mut serial, delay)) = Hc08::try_new(sim.clone(), NoDelay) else {
+        panic!("a module at another baud rate answered");
+    };
+    assert!(matches!(error, Error::Timeout));
+
+    serial
+        .reconfigure(BaudRate::Bps115200, Parity::None)
+        .unwrap();
+    assert!(Hc08::try_new(serial, delay).is_ok());
+}
+
+#[test]
+fn attach_hands_the_port_back_on_failure() {
+    let sim = Hc08Sim::with_state(SimState {
+        baud_rate: BaudRate::Bps115200,
+        ..SimState::default()
+    });
+
+    let Err((error, mut serial, delay)) = Hc08::attach(sim.clone(), NoDelay) else {
+        panic!("a module at another baud rate answered");
+    };
+    assert!(matches!(error, Error::Timeout));
+
+    serial
+        .reconfigure(BaudRate::Bps115200, Parity::None)
+        .unwrap();
+    assert!(Hc08::attach(serial, delay).is_ok());
+}
+
+#[test]
+fn serial_config_is_audited_and_fixed() {
+    let sim = Hc08Sim::with_state(SimState {
+        parity: Parity::Odd,
+        ..SimState::default()
+    });
+    sim.clone()
+        .reconfigure(BaudRate::Bps9600, Parity::Odd)
+        .unwrap();
+
+    let mut hc08 = Hc08::attach(sim.clone(), NoDelay).unwrap();
+    assert_eq!(hc08.query_serial_config().unwrap().parity, Parity::Odd);
+
+    hc08.change_serial_config(SerialConfig::default()).unwrap();
+    assert_eq!(hc08.query_serial_config().unwrap(), SerialConfig::default());
+    assert_eq!(sim.host_settings(), (BaudRate::Bps9600, Parity::None));
+}
+
+#[test]
+fn addr_is_assigned_and_read_back() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    assert_eq!(hc08.query_addr().unwrap(), sim.state().addr);
+
+    let addr: Addr = "C0:FF:EE:00:00:01".parse().unwrap();
+    hc08.change_addr(addr).unwrap();
+    assert_eq!(sim.state().addr, addr);
+    assert_eq!(hc08.query_addr().unwrap(), addr);
+    assert_eq!(hc08.get_parameters().unwrap().addr, addr);
+}
+
+#[test]
+fn rf_power_levels() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    assert_eq!(hc08.query_rf_power().unwrap(), RfPower::Dbm4);
+
+    for power in [RfPower::Dbm0, RfPower::DbmMinus6, RfPower::DbmMinus23] {
+        hc08.change_rf_power(power).unwrap();
+        assert_eq!(sim.state().rf_power, power);
+        assert_eq!(hc08.query_rf_power().unwrap(), power);
+    }
+    assert_eq!(
+        hc08.get_parameters().unwrap().rf_power,
+        Some(RfPower::DbmMinus23)
+    );
+}
+
+#[test]
+fn power_mode_and_auto_sleep() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    assert_eq!(hc08.query_power_mode().unwrap(), PowerMode::FullSpeed);
+
+    hc08.change_power_mode(PowerMode::LowPower2).unwrap();
+    hc08.change_auto_sleep(30).unwrap();
+    assert_eq!(hc08.query_power_mode().unwrap(), PowerMode::LowPower2);
+    assert_eq!(sim.state().auto_sleep, 30);
+    assert!(matches!(hc08.change_auto_sleep(0), Err(Error::OutOfRange)));
+}
+
+#[test]
+fn sleeping_module_is_woken_before_commands() {
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+
+    // At full speed the module never sleeps, so the handle is kept.
+    let Err(mut hc08) = hc08.into_sleeping() else {
+        panic!("a module at full speed was put to sleep");
+    };
+    hc08.change_power_mode(PowerMode::LowPower2).unwrap();
+    hc08.set_timeout(10);
+
+    let sleeping = hc08.into_sleeping().ok().unwrap();
+    sim.fall_asleep();
+    assert!(sim.is_asleep());
+
+    let mut hc08 = sleeping.wake().ok().unwrap();
+    assert!(!sim.is_asleep());
+    assert!(hc08.is_ok());
+    assert_eq!(hc08.query_power_mode().unwrap(), PowerMode::LowPower2);
+}
+
+#[test]
+fn sleeping_module_can_be_released() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    hc08.change_power_mode(PowerMode::LowPower2).unwrap();
+
+    let sleeping = hc08.into_sleeping().ok().unwrap();
+    sim.fall_asleep();
+    let sent = sim.history().len();
+
+    let (_serial, _delay) = sleeping.release();
+    assert!(sim.is_asleep());
+    assert_eq!(sim.history().len(), sent);
+}
+
+#[test]
+fn advertising_interval() {
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    let mut hc08 = hc08.into_broadcast_mode().ok().unwrap();
+    assert_eq!(hc08.query_advertising_interval().unwrap(), 100);
+
+    hc08.change_advertising_interval(1000).unwrap();
+    assert_eq!(sim.state().advertising_interval, 1600);
+    assert_eq!(hc08.query_advertising_interval().unwrap(), 1000);
+
+    assert!(matches!(
+        hc08.change_advertising_interval(19),
+        Err(Error::OutOfRange)
+    ));
+    assert!(matches!(
+        hc08.change_advertising_interval(10241),
+        Err(Error::OutOfRange)
+    ));
+}
+
+#[test]
+fn reboot_keeps_settings_and_drains_the_banner() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    hc08.change_name("kept").unwrap();
+
+    hc08.reboot().unwrap();
+    assert_eq!(sim.history().last().unwrap(), b"AT");
+    assert_eq!(hc08.timeout(), DEFAULT_TIMEOUT_MS);
+    assert_eq!(sim.state().name, "kept");
+
+    let mut buffer = [0u8; 21];
+    assert_eq!(hc08.get_version(&mut buffer).unwrap(), VERSION);
+}
+
+#[test]
+fn reboot_takes_a_banner_right_behind_ok() {
+    for reads in [0, 1, LINE_GAP_MS / 2] {
+        let sim = Hc08Sim::new();
+        let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+        sim.set_boot_reads(reads);
+
+        hc08.reboot().unwrap();
+        assert_eq!(sim.history().last().unwrap(), b"AT");
+
+        let mut buffer = [0u8; 21];
+        assert_eq!(hc08.get_version(&mut buffer).unwrap(), VERSION);
+    }
+}
+
+#[test]
+fn led_mode() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    assert_eq!(hc08.query_led_mode().unwrap(), LedMode::On);
+
+    hc08.change_led_mode(LedMode::Off).unwrap();
+    assert_eq!(sim.state().led_mode, LedMode::Off);
+    assert_eq!(hc08.query_led_mode().unwrap(), LedMode::Off);
+    assert_eq!(hc08.get_parameters().unwrap().led_mode, Some(LedMode::Off));
+}
+
+#[test]
+fn verify_mode_catches_ignored_changes() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    hc08.set_verify(true);
+    hc08.change_name("sensor-1").unwrap();
+    hc08.change_rf_power(RfPower::Dbm0).unwrap();
+    assert_eq!(sim.history().last().unwrap(), b"AT+RFPM=?");
+
+    let sent = sim.history().len();
+    assert!(matches!(
+        hc08.change_name("a-name-well-over-twenty-bytes"),
+        Err(Error::ParseError(ParseError::TooLong))
+    ));
+    assert_eq!(sim.history().len(), sent);
+    assert_eq!(sim.state().name, "sensor-1");
+
+    sim.set_ignore_changes(true);
+    assert!(matches!(
+        hc08.change_name("sensor-2"),
+        Err(Error::VerifyMismatch {
+            expected: Setting::Name(expected),
+            actual: Setting::Name(actual),
+        }) if expected.as_str() == "sensor-2" && actual.as_str() == "sensor-1"
+    ));
+    assert!(matches!(
+        hc08.change_role(Role::Slave),
+        Err(Error::VerifyMismatch {
+            expected: Setting::Role(Role::Slave),
+            actual: Setting::Role(Role::Master),
+        })
+    ));
+
+    // Without verify mode the acknowledgement is all that is checked.
+    hc08.set_verify(false);
+    hc08.change_role(Role::Slave).unwrap();
+    assert_eq!(sim.history().last().unwrap(), b"AT+ROLE=S");
+}
+
+#[test]
+fn verify_mode_reads_broadcast_data_back() {
+    let sim = Hc08Sim::new();
+    let hc08 = Hc08::try_new(sim.clone(), NoDelay).unwrap();
+    let mut hc08 = hc08.into_broadcast_mode().ok().unwrap();
+    hc08.set_verify(true);
+
+    hc08.change_broadcast_data(b"beacon").unwrap();
+    assert_eq!(sim.state().broadcast_data, b"beacon");
+    hc08.change_broadcast_data(b"\x01\xff\x00\xfe").unwrap();
+    assert_eq!(sim.state().broadcast_data, b"\x01\xff\x00\xfe");
+
+    // Data too long to read back is refused before it is sent.
+    let sent = sim.history().len();
+    assert!(matches!(
+        hc08.change_broadcast_data(&[b'x'; 33]),
+        Err(Error::ParseError(ParseError::TooLong))
+    ));
+    assert_eq!(sim.history().len(), sent);
+
+    sim.set_ignore_changes(true);
+    assert!(matches!(
+        hc08.change_broadcast_data(b"other"),
+        Err(Error::VerifyMismatch { .. })
+    ));
+}
+
+/// A command the driver has no method for, defined outside the crate.
+struct QueryVersionText;
+
+impl AtCommand for QueryVersionText {
+    type Response = Version;
+    type Partial = Option<Version>;
+
+    fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], ParseError> {
+        let command = b"AT+VERSION";
+        let buffer = buffer.get_mut(..command.len()).ok_or(ParseError::TooLong)?;
+        buffer.copy_from_slice(command);
+        Ok(buffer)
+    }
+
+    fn decode(&self, partial: &mut Option<Version>, line: Line<'_>) -> Result<(), ParseError> {
+        if partial.is_some() {
+            return Err(ParseError::WrongValue);
+        }
+        *partial = Some(Version::try_from(line.as_str()?)?);
+        Ok(())
+    }
+
+    fn finish(&self, partial: Option<Version>) -> Result<Version, ParseError> {
+        partial.ok_or(ParseError::MissingValue)
+    }
+}
+
+#[test]
+fn commands_run_through_execute() {
+    let sim = Hc08Sim::new();
+    let mut hc08 = Hc08::attach(sim.clone(), NoDelay).unwrap();
+
+    assert_eq!(hc08.execute(&QueryRfPower).unwrap(), RfPower::Dbm4);
+    assert_eq!(hc08.execute(&QueryVersionText).unwrap().as_str(), VERSION);
+    assert_eq!(sim.history().last().unwrap(), b"AT+VERSION");
+
+    assert!(matches!(
+        hc08.execute(&ChangeName("")),
+        Err(Error::Rejected)
+    ));
+    assert!(hc08.is_ok());
+}